@@ -0,0 +1,344 @@
+use crate::env;
+use crate::engines::models::engine::{EngineInstall, EngineSource};
+use crate::misc::errors::Verror::EngineResolutionError;
+use crate::misc::errors::{ErrorLevel, Result};
+use crate::misc::prelude::log;
+use crate::projects::models::project::{EngineAssociation, Project};
+use crate::settings::actions::settings_manager;
+use log::{error, info};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use tauri::{command, AppHandle, Manager};
+
+/// # Engine Registry Actions
+/// This module discovers the Unreal Engine installations of the user from
+/// the Epic Games Launcher manifests, the Install.ini registration table and
+/// the custom engines declared in the settings, and resolves the engine
+/// association of projects to one of these installations.
+
+/// Entry of the LauncherInstalled.dat installation list
+#[derive(Debug, Deserialize)]
+struct LauncherInstallation {
+    #[serde(rename = "InstallLocation")]
+    install_location: String,
+    #[serde(rename = "AppName")]
+    app_name: String,
+}
+
+/// Content of the LauncherInstalled.dat file
+#[derive(Debug, Deserialize)]
+struct LauncherInstalledFile {
+    #[serde(rename = "InstallationList", default)]
+    installation_list: Vec<LauncherInstallation>,
+}
+
+/// Content of an Epic Games Launcher .item manifest (only the fields we need)
+#[derive(Debug, Deserialize)]
+struct LauncherItemManifest {
+    #[serde(rename = "InstallLocation")]
+    install_location: String,
+    #[serde(rename = "AppName")]
+    app_name: String,
+    #[serde(rename = "DisplayName", default)]
+    display_name: Option<String>,
+}
+
+/// List all the engine installations found on the system
+#[command]
+pub fn list_engines(app_handle: AppHandle) -> Result<Vec<EngineInstall>> {
+    let engines = discover_engines(&app_handle);
+
+    log(
+        &app_handle,
+        ErrorLevel::Info,
+        &format!("Found {} engine installation(s).", engines.len()),
+    );
+
+    Ok(engines)
+}
+
+/// Resolve the engine installation used by a project
+#[command]
+pub fn resolve_project_engine(app_handle: AppHandle, project_path: String) -> Result<EngineInstall> {
+    let project_path = Project::resolve_path(&app_handle, &project_path);
+
+    let association = read_engine_association(&project_path)?;
+
+    match resolve_engine(&app_handle, &association, &project_path) {
+        Ok(engine) => {
+            info!(
+                "Resolved engine of {} to {}",
+                project_path.display(),
+                engine.root.display()
+            );
+            Ok(engine)
+        }
+        Err(e) => {
+            error!("{}", e);
+            log(&app_handle, ErrorLevel::Error, &e.to_string());
+            Err(e)
+        }
+    }
+}
+
+/// Read the engine association of a project directly from its .uproject file
+pub fn read_engine_association(project_path: &Path) -> Result<EngineAssociation> {
+    let contents = std::fs::read_to_string(project_path)?;
+    let uproject_content: serde_json::Value = serde_json::from_str(&contents)?;
+
+    Ok(EngineAssociation::from_uproject(&uproject_content))
+}
+
+/// Discover every engine installation available on the system.
+/// Engines found by several sources are only listed once (first source wins).
+pub fn discover_engines(app_handle: &AppHandle) -> Vec<EngineInstall> {
+    let mut engines: Vec<EngineInstall> = Vec::new();
+
+    let discovered = discover_launcher_engines(app_handle)
+        .into_iter()
+        .chain(discover_install_ini_engines(app_handle))
+        .chain(discover_custom_engines(app_handle));
+
+    for engine in discovered {
+        let root = engine.root.canonicalize().unwrap_or_else(|_| engine.root.clone());
+        let already_known = engines.iter().any(|known| {
+            known.root.canonicalize().unwrap_or_else(|_| known.root.clone()) == root
+        });
+
+        if !already_known {
+            engines.push(engine);
+        }
+    }
+
+    engines
+}
+
+/// Resolve an engine association to an engine installation.
+/// - Standard associations match the engine id first, then the version of launcher engines, then any engine version.
+/// - GUID associations match the GUIDs registered in Install.ini.
+/// - Custom associations look for an engine root enclosing the project (native projects).
+pub fn resolve_engine(
+    app_handle: &AppHandle,
+    association: &EngineAssociation,
+    project_path: &Path,
+) -> Result<EngineInstall> {
+    // Projects stored before GUID associations were recognized hold them as Standard
+    let association = match association {
+        EngineAssociation::Standard(value) => EngineAssociation::parse(value),
+        other => other.clone(),
+    };
+
+    let resolved = match &association {
+        EngineAssociation::Standard(version) => {
+            let engines = discover_engines(app_handle);
+            engines
+                .iter()
+                .find(|engine| engine.id == *version)
+                .or_else(|| {
+                    engines.iter().find(|engine| {
+                        engine.source == EngineSource::Launcher && engine.matches_version(version)
+                    })
+                })
+                .or_else(|| engines.iter().find(|engine| engine.matches_version(version)))
+                .cloned()
+        }
+        EngineAssociation::Guid(guid) => {
+            let guid = EngineAssociation::normalize_guid(guid);
+            discover_engines(app_handle)
+                .into_iter()
+                .find(|engine| {
+                    EngineAssociation::is_guid(&engine.id)
+                        && EngineAssociation::normalize_guid(&engine.id) == guid
+                })
+        }
+        EngineAssociation::Custom => find_enclosing_engine(project_path).and_then(|root| {
+            EngineInstall::try_from_root(
+                "Custom".to_string(),
+                root.file_name()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or("Custom")
+                    .to_string(),
+                &root,
+                EngineSource::Custom,
+            )
+            .ok()
+        }),
+    };
+
+    resolved.ok_or_else(|| EngineResolutionError(association.to_string()))
+}
+
+/// Find the engine root containing a project, if any.
+/// Projects without engine association are expected to live inside the engine tree.
+fn find_enclosing_engine(project_path: &Path) -> Option<PathBuf> {
+    project_path
+        .ancestors()
+        .skip(1)
+        .find(|dir| dir.join(env::ENGINE_BUILD_VERSION_PATH).exists())
+        .map(Path::to_path_buf)
+}
+
+/// Directory where the Epic Games Launcher stores its data
+fn launcher_data_dir(app_handle: &AppHandle) -> Option<PathBuf> {
+    if cfg!(target_os = "windows") {
+        std::env::var("PROGRAMDATA").ok().map(PathBuf::from)
+    } else {
+        app_handle.path().config_dir().ok()
+    }
+}
+
+/// Discover engines installed through the Epic Games Launcher
+fn discover_launcher_engines(app_handle: &AppHandle) -> Vec<EngineInstall> {
+    let mut engines = Vec::new();
+
+    let Some(data_dir) = launcher_data_dir(app_handle) else {
+        return engines;
+    };
+
+    // Step 1: Read the installation list maintained by the launcher
+    let installed_path = data_dir.join(env::ENGINE_LAUNCHER_INSTALLED_PATH);
+    if let Ok(contents) = std::fs::read_to_string(&installed_path) {
+        match serde_json::from_str::<LauncherInstalledFile>(&contents) {
+            Ok(installed) => {
+                for installation in installed.installation_list {
+                    if let Some(engine) = launcher_engine(
+                        &installation.app_name,
+                        None,
+                        &installation.install_location,
+                    ) {
+                        engines.push(engine);
+                    }
+                }
+            }
+            Err(e) => {
+                error!("Failed to parse {}: {}", installed_path.display(), e);
+            }
+        }
+    }
+
+    // Step 2: Read the item manifests, which also list engines missing from the installation list
+    let manifests_pattern = format!(
+        "{}/*.item",
+        data_dir.join(env::ENGINE_LAUNCHER_MANIFESTS_PATH).display()
+    );
+    if let Ok(entries) = glob::glob(&manifests_pattern) {
+        for manifest_path in entries.flatten() {
+            let manifest = std::fs::read_to_string(&manifest_path)
+                .ok()
+                .and_then(|contents| serde_json::from_str::<LauncherItemManifest>(&contents).ok());
+
+            if let Some(manifest) = manifest {
+                if let Some(engine) = launcher_engine(
+                    &manifest.app_name,
+                    manifest.display_name,
+                    &manifest.install_location,
+                ) {
+                    engines.push(engine);
+                }
+            }
+        }
+    }
+
+    engines
+}
+
+/// Build a launcher engine from a launcher application entry, ignoring non-engine applications
+fn launcher_engine(
+    app_name: &str,
+    display_name: Option<String>,
+    install_location: &str,
+) -> Option<EngineInstall> {
+    let version = app_name.strip_prefix(env::ENGINE_LAUNCHER_APP_PREFIX)?;
+    let name = display_name.unwrap_or_else(|| format!("Unreal Engine {}", version));
+
+    EngineInstall::try_from_root(
+        version.to_string(),
+        name,
+        Path::new(install_location),
+        EngineSource::Launcher,
+    )
+    .ok()
+}
+
+/// Discover engines registered in the [Installations] section of Install.ini
+fn discover_install_ini_engines(app_handle: &AppHandle) -> Vec<EngineInstall> {
+    let Ok(config_dir) = app_handle.path().config_dir() else {
+        return Vec::new();
+    };
+
+    let install_ini_path = config_dir.join(env::ENGINE_INSTALL_INI_PATH);
+    let Ok(contents) = std::fs::read_to_string(&install_ini_path) else {
+        return Vec::new();
+    };
+
+    parse_ini_section(&contents, "Installations")
+        .into_iter()
+        .filter_map(|(id, root)| {
+            let name = Path::new(&root)
+                .file_name()
+                .and_then(|s| s.to_str())
+                .unwrap_or(&id)
+                .to_string();
+
+            match EngineInstall::try_from_root(id.clone(), name, Path::new(&root), EngineSource::InstallIni) {
+                Ok(engine) => Some(engine),
+                Err(e) => {
+                    error!("Ignoring engine {} registered in Install.ini: {}", id, e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Discover the custom engines declared by the user in the settings
+fn discover_custom_engines(app_handle: &AppHandle) -> Vec<EngineInstall> {
+    let settings = match settings_manager::load_settings(app_handle) {
+        Ok(settings) => settings,
+        Err(e) => {
+            error!("Failed to load settings while discovering engines: {}", e);
+            return Vec::new();
+        }
+    };
+
+    settings
+        .engine_programs
+        .custom_engines
+        .into_iter()
+        .filter(|(_, root)| !root.is_empty())
+        .filter_map(|(name, root)| {
+            match EngineInstall::try_from_root(name.clone(), name.clone(), Path::new(&root), EngineSource::Custom) {
+                Ok(engine) => Some(engine),
+                Err(e) => {
+                    error!("Ignoring custom engine {}: {}", name, e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Read the key/value pairs of a section of an ini file
+fn parse_ini_section(contents: &str, section: &str) -> Vec<(String, String)> {
+    let mut entries = Vec::new();
+    let mut in_section = false;
+
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+            continue;
+        }
+
+        if line.starts_with('[') && line.ends_with(']') {
+            in_section = &line[1..line.len() - 1] == section;
+            continue;
+        }
+
+        if in_section {
+            if let Some((key, value)) = line.split_once('=') {
+                entries.push((key.trim().to_string(), value.trim().to_string()));
+            }
+        }
+    }
+
+    entries
+}
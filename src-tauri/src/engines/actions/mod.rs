@@ -0,0 +1 @@
+pub mod engine_registry;
\ No newline at end of file
@@ -0,0 +1,153 @@
+use crate::env;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Describes where an engine installation has been discovered from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum EngineSource {
+    Launcher,   // Installed through the Epic Games Launcher
+    InstallIni, // Registered in the [Installations] table of Install.ini (source builds)
+    Custom,     // Declared by the user in the settings (EnginePrograms::custom_engines)
+}
+
+/// Version of an engine installation, read from the Engine/Build/Build.version file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EngineVersion {
+    pub major: u32,          // Major version (4, 5, ...)
+    pub minor: u32,          // Minor version (27, 3, ...)
+    pub patch: u32,          // Hotfix version
+    pub changelist: u64,     // Changelist the engine has been built from (0 for local builds)
+    pub branch_name: String, // Name of the branch the engine has been built from
+}
+
+/// Raw content of a Build.version file.
+#[derive(Debug, Deserialize)]
+struct BuildVersionFile {
+    #[serde(rename = "MajorVersion")]
+    major_version: u32,
+    #[serde(rename = "MinorVersion")]
+    minor_version: u32,
+    #[serde(rename = "PatchVersion", default)]
+    patch_version: u32,
+    #[serde(rename = "Changelist", default)]
+    changelist: u64,
+    #[serde(rename = "BranchName", default)]
+    branch_name: String,
+}
+
+/// An Unreal Engine installation found on the user's file system.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EngineInstall {
+    pub id: String,                      // Identifier matched against EngineAssociation (version, GUID or custom name)
+    pub name: String,                    // Display name of the engine
+    pub root: PathBuf,                   // Engine root directory (the folder containing Engine/)
+    pub version: Option<EngineVersion>,  // Version read from Build.version (None if the file is missing or invalid)
+    pub source: EngineSource,            // Where the engine has been discovered from
+}
+
+impl EngineVersion {
+    /// Reads the version of the engine located at the given root directory.
+    pub fn try_from_root(root: &Path) -> Result<EngineVersion, Box<dyn std::error::Error>> {
+        let contents = std::fs::read_to_string(root.join(env::ENGINE_BUILD_VERSION_PATH))?;
+        let build_version: BuildVersionFile = serde_json::from_str(&contents)?;
+
+        Ok(EngineVersion {
+            major: build_version.major_version,
+            minor: build_version.minor_version,
+            patch: build_version.patch_version,
+            changelist: build_version.changelist,
+            branch_name: build_version.branch_name,
+        })
+    }
+
+    /// Returns the full "major.minor.patch" form of the version (e.g. "5.3.2")
+    pub fn full_version(&self) -> String {
+        format!("{}.{}.{}", self.major, self.minor, self.patch)
+    }
+
+    /// Returns the "major.minor" form used by standard engine associations (e.g. "5.3")
+    pub fn short_version(&self) -> String {
+        format!("{}.{}", self.major, self.minor)
+    }
+}
+
+impl EngineInstall {
+    /// Creates an engine installation from its root directory.
+    /// Fails if the directory does not look like an engine root (no Engine/ folder).
+    pub fn try_from_root(
+        id: String,
+        name: String,
+        root: &Path,
+        source: EngineSource,
+    ) -> Result<EngineInstall, Box<dyn std::error::Error>> {
+        if !root.join("Engine").is_dir() {
+            return Err(format!("{} is not an engine directory", root.display()).into());
+        }
+
+        // An engine without a readable Build.version is still usable, only its version is unknown
+        let version = EngineVersion::try_from_root(root).ok();
+
+        Ok(EngineInstall {
+            id,
+            name,
+            root: root.to_path_buf(),
+            version,
+            source,
+        })
+    }
+
+    /// Returns the path of the editor executable of this engine.
+    /// Engines 4.x ship UE4Editor while engines 5.x ship UnrealEditor.
+    pub fn editor_binary(&self) -> PathBuf {
+        let editor_name = match &self.version {
+            Some(version) if version.major < 5 => "UE4Editor",
+            Some(_) => "UnrealEditor",
+            // Without a version, pick the binary that exists (prefer UnrealEditor)
+            None if !self.binary_path("UnrealEditor").exists()
+                && self.binary_path("UE4Editor").exists() => "UE4Editor",
+            None => "UnrealEditor",
+        };
+
+        self.binary_path(editor_name)
+    }
+
+    /// Returns the path of an executable in the Binaries folder of the current platform.
+    fn binary_path(&self, binary_name: &str) -> PathBuf {
+        let binaries_dir = self.root.join("Engine").join("Binaries");
+
+        if cfg!(target_os = "windows") {
+            binaries_dir.join("Win64").join(format!("{}.exe", binary_name))
+        } else if cfg!(target_os = "macos") {
+            binaries_dir
+                .join("Mac")
+                .join(format!("{}.app", binary_name))
+                .join("Contents")
+                .join("MacOS")
+                .join(binary_name)
+        } else {
+            binaries_dir.join("Linux").join(binary_name)
+        }
+    }
+
+    /// Returns the path of the Unreal Build Tool script of the current platform
+    /// (Build.bat on Windows, Build.sh elsewhere).
+    pub fn build_script(&self) -> PathBuf {
+        let script_path = if cfg!(target_os = "windows") {
+            env::UBT_BUILD_SCRIPT_WINDOWS_PATH
+        } else if cfg!(target_os = "macos") {
+            env::UBT_BUILD_SCRIPT_MAC_PATH
+        } else {
+            env::UBT_BUILD_SCRIPT_LINUX_PATH
+        };
+
+        self.root.join(script_path)
+    }
+
+    /// Checks if this engine matches a standard "major.minor" association.
+    pub fn matches_version(&self, association: &str) -> bool {
+        self.version
+            .as_ref()
+            .map(|version| version.short_version() == association)
+            .unwrap_or(false)
+    }
+}
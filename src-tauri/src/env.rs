@@ -25,6 +25,58 @@ pub(crate) const STORE_PROJECTS_KEY: &str = "projects";
 /// The key used to store settings in the store
 pub(crate) const STORE_SETTINGS_KEY: &str = "app_settings";
 
+/// The key used to store the finished usage sessions of the projects
+pub(crate) const STORE_SESSIONS_KEY: &str = "project_sessions";
+
+/// The key used to store the history of the finished background tasks
+pub(crate) const STORE_TASK_HISTORY_KEY: &str = "task_history";
+
+/// The key used to store the state of the backup schedules (last runs and archives created)
+pub(crate) const STORE_BACKUP_STATES_KEY: &str = "backup_states";
+
+/// The key used to store the version of the schema of the store (see `misc::store_schema`)
+pub(crate) const STORE_SCHEMA_VERSION_KEY: &str = "schema_version";
+
+///# ====================================
+///# == Engine discovery configuration
+///# ====================================
+/// Locations used to discover the Unreal Engine installations of the user.
+
+/// Path of the engine version file, relative to an engine root directory
+pub(crate) const ENGINE_BUILD_VERSION_PATH: &str = "Engine/Build/Build.version";
+
+/// Path of the file listing the engines registered by source builds (Linux and macOS),
+/// relative to the user's config directory. Engines are listed in its `[Installations]` section.
+pub(crate) const ENGINE_INSTALL_INI_PATH: &str = "Epic/UnrealEngine/Install.ini";
+
+/// Path of the Epic Games Launcher installation list, relative to the launcher data directory
+/// (ProgramData on Windows, the user's config directory elsewhere)
+pub(crate) const ENGINE_LAUNCHER_INSTALLED_PATH: &str = "Epic/UnrealEngineLauncher/LauncherInstalled.dat";
+
+/// Path of the Epic Games Launcher item manifests, relative to the launcher data directory
+pub(crate) const ENGINE_LAUNCHER_MANIFESTS_PATH: &str = "Epic/EpicGamesLauncher/Data/Manifests";
+
+/// Prefix of the launcher application names that are engine installations (e.g. "UE_5.3")
+pub(crate) const ENGINE_LAUNCHER_APP_PREFIX: &str = "UE_";
+
+///# ====================================
+///# == Unreal Build Tool configuration
+///# ====================================
+/// Scripts used to run UnrealBuildTool, relative to an engine root directory.
+
+/// Path of the build script on Windows
+pub(crate) const UBT_BUILD_SCRIPT_WINDOWS_PATH: &str = "Engine/Build/BatchFiles/Build.bat";
+
+/// Path of the build script on macOS
+pub(crate) const UBT_BUILD_SCRIPT_MAC_PATH: &str = "Engine/Build/BatchFiles/Mac/Build.sh";
+
+/// Path of the build script on Linux
+pub(crate) const UBT_BUILD_SCRIPT_LINUX_PATH: &str = "Engine/Build/BatchFiles/Linux/Build.sh";
+
+/// Process creation flag keeping the helper console programs (taskkill) from opening a window
+#[cfg(windows)]
+pub(crate) const WINDOWS_CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
 ///# ====================================
 ///# == Logging configuration
 ///# ====================================
@@ -100,4 +152,135 @@ pub(crate) const EVENT_ADD_LOG: &str = "add_log";
 pub(crate) const EVENT_PROJECTS_UPDATED: &str = "projects_updated";
 
 /// Background task progress events
-pub(crate) const EVENT_TASK_PROGRESS: &str = "task_progress";
\ No newline at end of file
+pub(crate) const EVENT_TASK_PROGRESS: &str = "task_progress";
+
+/// Process supervision events, fired when a launched process starts or exits
+pub(crate) const EVENT_PROCESS_STARTED: &str = "process_started";
+
+pub(crate) const EVENT_PROCESS_EXITED: &str = "process_exited";
+
+/// Build events, fired for each error or warning found in the output of UnrealBuildTool
+pub(crate) const EVENT_BUILD_DIAGNOSTIC: &str = "build_diagnostic";
+
+///# ====================================
+///# == Process supervision configuration
+///# ====================================
+
+/// Interval (in milliseconds) at which the launched processes are checked for exit
+pub(crate) const PROCESS_MONITOR_INTERVAL_MS: u64 = 1000;
+
+/// Maximum number of finished usage sessions kept in the store (the oldest ones are dropped)
+pub(crate) const SESSIONS_MAX_RECORDS: usize = 5000;
+
+///# ====================================
+///# == Task configuration
+///# ====================================
+
+/// Interval (in milliseconds) at which the tasks waiting on an external process check for cancellation
+pub(crate) const TASK_CANCELLATION_POLL_INTERVAL_MS: u64 = 200;
+
+/// Maximum number of finished tasks kept in the history (the oldest ones are dropped)
+pub(crate) const TASK_HISTORY_MAX_RECORDS: usize = 500;
+
+///# ====================================
+///# == Archive configuration
+///# ====================================
+/// Archives are written in-process (see `archive_writer`), these values tune the writer.
+
+/// Size (in bytes) of the buffer used to read the files added to an archive
+pub(crate) const ARCHIVE_BUFFER_SIZE: usize = 1024 * 1024;
+
+/// Minimum interval (in milliseconds) between two progress events of an archive task
+pub(crate) const ARCHIVE_PROGRESS_INTERVAL_MS: u64 = 250;
+
+/// Default compression level of the Zstandard archives (1 to 22)
+pub(crate) const ARCHIVE_ZSTD_LEVEL: i32 = 9;
+
+/// Default compression level of the deflate (zip) and gzip archives (0 to 9)
+pub(crate) const ARCHIVE_DEFLATE_LEVEL: i32 = 6;
+
+/// Default LZMA2 preset of the 7z archives (0 to 9)
+pub(crate) const ARCHIVE_LZMA_PRESET: i32 = 6;
+
+/// Largest file (in bytes) compressed in memory by the worker threads of a zip archive, bigger files are streamed
+pub(crate) const ARCHIVE_PARALLEL_MAX_FILE_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Maximum size (in bytes) of the files compressed in memory at the same time for a zip archive
+pub(crate) const ARCHIVE_PARALLEL_BATCH_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Name of the manifest entry embedded at the root of the archives
+pub(crate) const ARCHIVE_MANIFEST_NAME: &str = "project_manager_manifest.json";
+
+/// Version of the manifest format, increased when its structure changes
+pub(crate) const ARCHIVE_MANIFEST_VERSION: u32 = 2;
+
+/// Directory of the destination an archive is extracted to before its files are moved into place
+pub(crate) const ARCHIVE_STAGING_DIR: &str = ".extracting";
+
+/// Smallest volume size (in bytes) accepted for split archives, to avoid creating thousands of files
+pub(crate) const ARCHIVE_MIN_VOLUME_SIZE: u64 = 1024 * 1024;
+
+/// Number of digits of the volume numbers of split archives (e.g. "Project.zip.001")
+pub(crate) const ARCHIVE_VOLUME_DIGITS: usize = 3;
+
+///# ====================================
+///# == Backup scheduling configuration
+///# ====================================
+/// Scheduled backups are run by the application while it is running (see `backup_scheduler`).
+
+/// Interval (in seconds) at which the backup schedules are checked, the first check is done after one interval
+pub(crate) const BACKUP_SCHEDULER_INTERVAL_SECS: u64 = 60;
+
+/// Delay (in seconds) before a failed scheduled backup is tried again
+pub(crate) const BACKUP_RETRY_DELAY_SECS: u64 = 30 * 60;
+
+/// Number of days searched for the next run of a schedule before considering it never runs
+pub(crate) const BACKUP_SCHEDULE_LOOKAHEAD_DAYS: u32 = 5 * 366;
+
+///# ====================================
+///# == Project discovery configuration
+///# ====================================
+/// Projects are searched by a parallel directory walker (see `discovery_walker`).
+
+/// Directories never searched for projects: build outputs, caches and version control data (any letter case)
+pub(crate) const DISCOVERY_PRUNED_DIRS: [&str; 15] = [
+    "Intermediate",
+    "DerivedDataCache",
+    "Binaries",
+    "Saved",
+    "node_modules",
+    ".git",
+    ".svn",
+    ".hg",
+    ".vs",
+    ".idea",
+    ".vscode",
+    "__pycache__",
+    "$RECYCLE.BIN",
+    "System Volume Information",
+    ARCHIVE_STAGING_DIR, // Extractions in progress
+];
+
+/// Maximum number of threads listing directories during a discovery
+pub(crate) const DISCOVERY_MAX_THREADS: usize = 8;
+
+/// Minimum interval (in milliseconds) between two progress events of a discovery
+pub(crate) const DISCOVERY_PROGRESS_INTERVAL_MS: u64 = 250;
+
+/// Delay (in milliseconds) the file system events of the library folders are gathered before being applied
+pub(crate) const LIBRARY_WATCHER_DEBOUNCE_MS: u64 = 500;
+
+///# ====================================
+///# == Project health configuration
+///# ====================================
+/// A project whose file has been deleted or moved is flagged as missing (see `project_health`).
+
+/// Files hashed to recognize a project after a move, relative to the project folder (the project file is always hashed)
+pub(crate) const PROJECT_IDENTITY_FILES: [&str; 3] = [
+    "Config/DefaultEngine.ini",
+    "Config/DefaultGame.ini",
+    "Config/DefaultEditor.ini",
+];
+
+/// Key of the hash of the project file in `Project::file_hashes`
+pub(crate) const PROJECT_FILE_HASH_KEY: &str = ".uproject";
\ No newline at end of file
@@ -16,6 +16,7 @@ mod misc;
 mod env;
 mod projects;
 mod settings;
+mod engines;
 
 // Learn more about Tauri commands at https://tauri.app/v1/guides/features/command
 #[tauri::command]
@@ -77,6 +78,15 @@ pub fn run() {
                 .build(),
         );
 
+    ///### Managed state
+    /// Register the state shared between the commands (see `tauri::State`).
+    let tauri_builder = tauri_builder
+        .manage(projects::actions::process_supervisor::ProcessSupervisor::default())
+        .manage(projects::actions::network_launcher::NetworkTestSessions::default())
+        .manage(misc::tasks::TaskManager::default())
+        .manage(projects::actions::backup_scheduler::BackupScheduler::default())
+        .manage(projects::actions::library_watcher::LibraryWatcher::default());
+
     ///### Application setup
     /// The setup function performe initialization tasks on startup such as:
     /// - Setting up the system tray
@@ -104,24 +114,55 @@ pub fn run() {
     /// - **See** the commands module to see the list of available commands.<br>
     let tauri_builder = tauri_builder.invoke_handler(tauri::generate_handler![
         greet,
+        misc::tasks::cancel_task,
+        misc::tasks::list_tasks,
+        misc::tasks::get_task_history,
         projects::actions::behavior::open_file_explorer,
         projects::actions::project_discovery::discover_projects,
         projects::actions::project_discovery::get_projects,
         projects::actions::project_discovery::remove_projects,
         projects::actions::project_discovery::rescan_projects,
+        projects::actions::project_health::check_projects_health,
+        projects::actions::project_health::relink_project,
         projects::actions::project_launcher::launch_project_with_engine,
+        projects::actions::project_launcher::launch_project_with_profile,
         projects::actions::project_launcher::launch_project_with_ide,
         projects::actions::project_launcher::launch_custom_engine_with_ide,
+        projects::actions::project_launcher::open_file_in_ide,
         projects::actions::project_launcher::project_has_cpp,
+        projects::actions::network_launcher::launch_network_test,
+        projects::actions::network_launcher::stop_network_test,
+        projects::actions::network_launcher::list_network_tests,
+        projects::actions::process_supervisor::list_running_processes,
+        projects::actions::process_supervisor::kill_process,
+        projects::actions::session_tracker::get_project_usage,
+        projects::actions::session_tracker::get_project_sessions,
         projects::actions::project_cleaner::clean_project,
+        projects::actions::project_lock::get_project_locks,
+        projects::actions::project_builder::build_project,
         projects::actions::project_compressor::compress_project,
         projects::actions::project_compressor::get_available_compression_algorithms,
+        projects::actions::project_extractor::extract_project_archive,
+        projects::actions::project_extractor::restore_project_backup,
+        projects::actions::archive_verifier::verify_archive,
+        projects::actions::backup_scheduler::get_backup_status,
+        projects::actions::backup_scheduler::run_backup_now,
         projects::actions::project_compressor::get_system_username,
         projects::actions::project_compressor::get_system_hostname,
         projects::actions::plugin_manager::scan_plugins,
         projects::actions::plugin_manager::refresh_all_plugins,
+        engines::actions::engine_registry::list_engines,
+        engines::actions::engine_registry::resolve_project_engine,
         settings::actions::settings_manager::get_settings,
         settings::actions::settings_manager::save_settings,
+        settings::actions::launch_profile_manager::get_launch_profiles,
+        settings::actions::launch_profile_manager::save_launch_profile,
+        settings::actions::launch_profile_manager::delete_launch_profile,
+        settings::actions::launch_profile_manager::export_launch_profiles,
+        settings::actions::launch_profile_manager::import_launch_profiles,
+        settings::actions::backup_schedule_manager::get_backup_schedule,
+        settings::actions::backup_schedule_manager::save_backup_schedule,
+        settings::actions::backup_schedule_manager::delete_backup_schedule,
         settings::actions::autostart_manager::enable_autostart,
         settings::actions::autostart_manager::disable_autostart,
         settings::actions::autostart_manager::is_autostart_enabled,
@@ -196,6 +237,9 @@ fn application_setup(app: &mut App) -> errors::Result<()> {
     //     }
     // }
 
+    // Start watching the processes launched by the app
+    projects::actions::process_supervisor::start_process_monitor(app.handle().clone());
+
     /// ### Initialize the store
     /// In this step, ensure that all entries in the store are initialized
     /// to avoid dealing with null values in the future.
@@ -207,9 +251,26 @@ fn application_setup(app: &mut App) -> errors::Result<()> {
             Ok(fetched_store) => {
                 store = fetched_store;
 
+                // Upgrade a store written by an older version of the app before using it.
+                // A store that cannot be migrated or read stops the app instead of losing its content.
+                if let Err(e) = misc::store_schema::migrate_store(app.handle()) {
+                    error!("The store cannot be used: {}", e);
+                    eprintln!("The store cannot be used. {}", e);
+                    return Err(e);
+                }
+
                 if store.get(env::STORE_PROJECTS_KEY).is_none() {
                     store.set(env::STORE_PROJECTS_KEY, json!([]));
                 }
+                if store.get(env::STORE_SESSIONS_KEY).is_none() {
+                    store.set(env::STORE_SESSIONS_KEY, json!([]));
+                }
+                if store.get(env::STORE_TASK_HISTORY_KEY).is_none() {
+                    store.set(env::STORE_TASK_HISTORY_KEY, json!([]));
+                }
+                if store.get(env::STORE_BACKUP_STATES_KEY).is_none() {
+                    store.set(env::STORE_BACKUP_STATES_KEY, json!([]));
+                }
                 info!("Store has been initialized.");
             }
             Err(e) => {
@@ -231,6 +292,15 @@ fn application_setup(app: &mut App) -> errors::Result<()> {
         }
     }
 
+    // Run the backup schedules, the backups missed while the app was closed are caught up
+    projects::actions::backup_scheduler::start_backup_scheduler(app.handle().clone());
+
+    // Watch the library folders, the projects changed while the app was closed are caught up
+    projects::actions::library_watcher::start_library_watcher(app.handle().clone());
+
+    // Flag the projects deleted or moved, and relink the ones found in the library folders
+    projects::actions::project_health::start_health_check(app.handle().clone());
+
     // Check if we should show the welcome popup
     match settings_manager::should_show_welcome_popup(app.handle()) {
         Ok(should_show) => {
@@ -248,7 +318,7 @@ fn application_setup(app: &mut App) -> errors::Result<()> {
 
     // Fire the app initialized event
     match app.emit(env::EVENT_INIT, AppInitializedPayload{
-        projects: Project::get_projects(app.handle())?
+        projects: Project::get_projects_with_usage(app.handle())?
     }) {
         Ok(_) => {
             info!("App initialized event emitted successfully, sent {} projects.", 
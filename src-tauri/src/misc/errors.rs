@@ -43,6 +43,18 @@ pub enum Verror {
     // STORE ERRORS
     #[error("An error occurred while fetching the store at {0}")]
     StoreAccessError(String),
+
+    // ENGINE ERRORS
+    #[error("No installed engine matches the engine association \"{0}\"")]
+    EngineResolutionError(String),
+
+    // PROJECT ERRORS
+    #[error("The project {0} is currently open ({1}). Close it first or force the operation.")]
+    ProjectLockedError(String, String),
+
+    // TASK ERRORS
+    #[error("The task \"{0}\" has been cancelled")]
+    TaskCancelledError(String),
 }
 
 // we must manually implement serde::Serialize
@@ -3,4 +3,6 @@ pub mod tray;
 pub mod menu;
 pub mod prelude;
 pub mod payloads;
-pub mod progress;
\ No newline at end of file
+pub mod progress;
+pub mod tasks;
+pub mod store_schema;
\ No newline at end of file
@@ -1,4 +1,6 @@
 use serde::{Deserialize, Serialize};
+use crate::projects::models::build::BuildDiagnostic;
+use crate::projects::models::process::RunningProcess;
 use crate::projects::models::project::Project;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,12 +35,49 @@ pub struct TaskProgressPayload {
     pub progress: f32, // 0.0 to 1.0
     pub status: TaskStatus,
     pub message: Option<String>,
+    pub started_at: u64,       // Start date of the task (seconds since UNIX epoch)
+    pub updated_at: u64,       // Date of this event (seconds since UNIX epoch)
+    pub eta_secs: Option<u64>, // Estimated remaining time (None if unknown)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum TaskStatus {
+    Queued,
     Started,
     InProgress,
     Completed,
     Failed,
+    Cancelled,
+}
+
+impl TaskStatus {
+    /// Check if the task is finished in this state
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, TaskStatus::Completed | TaskStatus::Failed | TaskStatus::Cancelled)
+    }
+
+    /// Check if a task can go from this state to another one.
+    /// Queued → Started → InProgress → Completed/Failed/Cancelled, a queued task can also fail or be cancelled.
+    pub fn can_transition_to(&self, next: &TaskStatus) -> bool {
+        match self {
+            TaskStatus::Queued => !matches!(next, TaskStatus::InProgress | TaskStatus::Completed),
+            TaskStatus::Started | TaskStatus::InProgress => {
+                !matches!(next, TaskStatus::Queued | TaskStatus::Started)
+            }
+            TaskStatus::Completed | TaskStatus::Failed | TaskStatus::Cancelled => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessExitedPayload {
+    pub process: RunningProcess,
+    pub exit_code: Option<i32>, // None if the process has been terminated by a signal
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildDiagnosticPayload {
+    pub task_id: String,      // Build task the diagnostic belongs to
+    pub project_path: String,
+    pub diagnostic: BuildDiagnostic,
 }
\ No newline at end of file
@@ -1,71 +1,346 @@
 use crate::env;
+use crate::misc::errors::Verror;
+use crate::misc::errors::Verror::TaskCancelledError;
 use crate::misc::payloads::{TaskProgressPayload, TaskStatus};
-use log::error;
-use tauri::{AppHandle, Emitter};
+use crate::misc::tasks::{self, CancellationToken, TaskInfo, TaskKind, TaskManager, TaskRecord};
+use crate::projects::models::project::Project;
+use crate::settings::actions::settings_manager;
+use log::{error, info, warn};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tauri::{AppHandle, Emitter, Manager};
 
-/// Helper struct to manage background task progress reporting
+/// Helper struct to manage background task progress reporting.
+///
+/// A task goes through the states Queued → Started → InProgress → Completed/Failed/Cancelled.
+/// Invalid transitions are ignored, so exactly one terminal event is emitted per task.
+/// A task can be split in weighted sub-tasks that report into a range of its progress.
 pub struct TaskProgress {
+    task: Arc<TaskState>,
+    offset: f32,           // Start of the range of the task progress covered by this progress
+    scale: f32,            // Part of the task progress covered by this progress (1.0 for the task itself)
+    label: Option<String>, // Name of the sub-task, prefixed to its messages (None for the task itself)
+}
+
+/// State of a task, shared between the task and its sub-tasks
+struct TaskState {
     app_handle: AppHandle,
     task_id: String,
     task_name: String,
+    kind: TaskKind,
+    project_id: Option<String>, // Identifier of the project the task works on, recorded in the history
+    cancellation: CancellationToken,
+    current: Mutex<CurrentState>,
+}
+
+struct CurrentState {
+    status: TaskStatus,
+    progress: f32,
+    started_at: u64,          // Start date (seconds since UNIX epoch), reset when a queued task starts
+    started_instant: Instant, // Used to estimate the remaining time
+    message: Option<String>,  // Last message reported
 }
 
 impl TaskProgress {
-    /// Create a new task progress tracker
+    /// Create a new task progress tracker, started immediately
     pub fn new(app_handle: AppHandle, task_id: String, task_name: String) -> Self {
-        let progress = Self {
-            app_handle,
-            task_id,
-            task_name,
-        };
-        
+        let progress = Self::register(app_handle, task_id, task_name, TaskKind::Other, None, true);
+
         // Emit task started event
-        progress.emit_progress(0.0, TaskStatus::Started, None);
+        progress.transition(TaskStatus::Started, 0.0, None);
         progress
     }
 
+    /// Create a new task progress tracker for a heavy operation. The task waits in the queue
+    /// until the concurrency limit of its kind allows it to start (see `TaskSettings`).
+    pub async fn queued(
+        app_handle: AppHandle,
+        task_id: String,
+        task_name: String,
+        kind: TaskKind,
+        project_path: Option<String>,
+    ) -> crate::misc::errors::Result<Self> {
+        let concurrency_limit = settings_manager::load_settings(&app_handle)
+            .map(|settings| settings.tasks.concurrency_limit(&kind))
+            .unwrap_or(Some(1));
+        let progress = Self::register(app_handle.clone(), task_id, task_name, kind, project_path, false);
+
+        if let Some(manager) = app_handle.try_state::<TaskManager>() {
+            let mut queued = false;
+            loop {
+                let released = manager.released();
+                if progress.is_cancelled() {
+                    progress.cancelled(Some("Cancelled while queued".to_string()));
+                    return Err(progress.cancellation_error());
+                }
+                if manager.try_start(&progress.task.task_id, concurrency_limit) {
+                    break;
+                }
+                if !queued {
+                    info!("Task {} queued", progress.task.task_id);
+                    progress.transition(TaskStatus::Queued, 0.0, Some("Waiting for other tasks...".to_string()));
+                    queued = true;
+                }
+                released.await;
+            }
+        }
+
+        // The duration of the task does not include the time spent in the queue
+        if let Ok(mut current) = progress.task.current.lock() {
+            current.started_at = tasks::now();
+            current.started_instant = Instant::now();
+        }
+        progress.transition(TaskStatus::Started, 0.0, None);
+        Ok(progress)
+    }
+
+    /// Register the task in the task manager, so it can be listed and cancelled from the frontend
+    fn register(
+        app_handle: AppHandle,
+        task_id: String,
+        task_name: String,
+        kind: TaskKind,
+        project_path: Option<String>,
+        started: bool,
+    ) -> Self {
+        let now = tasks::now();
+        let cancellation = match app_handle.try_state::<TaskManager>() {
+            Some(manager) => manager.register(TaskInfo {
+                task_id: task_id.clone(),
+                task_name: task_name.clone(),
+                kind: kind.clone(),
+                project_path: project_path.clone(),
+                status: TaskStatus::Queued,
+                progress: 0.0,
+                message: None,
+                queued_at: now,
+                started_at: if started { Some(now) } else { None },
+                eta_secs: None,
+            }),
+            None => CancellationToken::default(),
+        };
+
+        // The project is recorded by its identifier, its path may change before the task finishes
+        let project_id = project_path
+            .as_deref()
+            .and_then(|path| Project::find_id(&app_handle, Path::new(path)));
+
+        Self {
+            task: Arc::new(TaskState {
+                app_handle,
+                task_id,
+                task_name,
+                kind,
+                project_id,
+                cancellation,
+                // A task is created "queued", the first transition starts or queues it
+                current: Mutex::new(CurrentState {
+                    status: TaskStatus::Queued,
+                    progress: 0.0,
+                    started_at: now,
+                    started_instant: Instant::now(),
+                    message: None,
+                }),
+            }),
+            offset: 0.0,
+            scale: 1.0,
+            label: None,
+        }
+    }
+
+    /// Create a sub-task covering `weight` of this progress, starting at `start` (both from 0.0 to 1.0).
+    /// A sub-task shares the cancellation of its task; its outcome is reported by the parent.
+    pub fn sub_task(&self, start: f32, weight: f32, name: &str) -> TaskProgress {
+        TaskProgress {
+            task: self.task.clone(),
+            offset: self.offset + start.clamp(0.0, 1.0) * self.scale,
+            scale: weight.clamp(0.0, 1.0) * self.scale,
+            label: Some(match &self.label {
+                Some(label) => format!("{} > {}", label, name),
+                None => name.to_string(),
+            }),
+        }
+    }
+
     /// Update task progress
     pub fn update(&self, progress: f32, message: Option<String>) {
-        self.emit_progress(progress.clamp(0.0, 1.0), TaskStatus::InProgress, message);
+        let progress = self.offset + progress.clamp(0.0, 1.0) * self.scale;
+        self.transition(TaskStatus::InProgress, progress, self.label_message(message));
     }
 
     /// Mark task as completed
     pub fn complete(&self, message: Option<String>) {
-        self.emit_progress(1.0, TaskStatus::Completed, message);
+        if self.label.is_some() {
+            self.update(1.0, message);
+            return;
+        }
+        self.transition(TaskStatus::Completed, 1.0, message);
     }
 
     /// Mark task as failed
     pub fn fail(&self, message: Option<String>) {
-        self.emit_progress(0.0, TaskStatus::Failed, message);
+        if self.label.is_some() {
+            // The parent decides if the failure of a sub-task fails the whole task
+            self.report(message);
+            return;
+        }
+        self.transition(TaskStatus::Failed, self.current_progress(), message);
+    }
+
+    /// Mark task as cancelled
+    pub fn cancelled(&self, message: Option<String>) {
+        if self.label.is_some() {
+            self.report(message);
+            return;
+        }
+        self.transition(TaskStatus::Cancelled, self.current_progress(), message);
+    }
+
+    /// Check if the cancellation of the task has been requested
+    pub fn is_cancelled(&self) -> bool {
+        self.task.cancellation.is_cancelled()
+    }
+
+    /// Fail with a cancellation error if the cancellation of the task has been requested.
+    /// Long operations call this between their steps.
+    pub fn check_cancelled(&self) -> crate::misc::errors::Result<()> {
+        if self.is_cancelled() {
+            return Err(self.cancellation_error());
+        }
+        Ok(())
+    }
+
+    /// Get the error returned by the task when it has been cancelled
+    pub fn cancellation_error(&self) -> Verror {
+        TaskCancelledError(self.task.task_name.clone())
+    }
+
+    /// Get the cancellation token of the task, to check it from code that does not own the progress
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.task.cancellation.clone()
+    }
+
+    /// Report a message of a sub-task without changing the progress
+    fn report(&self, message: Option<String>) {
+        self.transition(TaskStatus::InProgress, self.current_progress(), self.label_message(message));
+    }
+
+    fn current_progress(&self) -> f32 {
+        self.task
+            .current
+            .lock()
+            .map(|current| current.progress)
+            .unwrap_or(0.0)
     }
 
-    /// Emit progress event to frontend
-    fn emit_progress(&self, progress: f32, status: TaskStatus, message: Option<String>) {
+    /// Prefix a message with the name of the sub-task
+    fn label_message(&self, message: Option<String>) -> Option<String> {
+        match (&self.label, message) {
+            (Some(label), Some(message)) => Some(format!("{}: {}", label, message)),
+            (_, message) => message,
+        }
+    }
+
+    /// Move the task to a new state and emit the progress event to the frontend.
+    /// Returns false if the transition is not allowed (e.g. an update after the task has finished).
+    fn transition(&self, status: TaskStatus, progress: f32, message: Option<String>) -> bool {
+        let Ok(mut current) = self.task.current.lock() else {
+            return false;
+        };
+
+        if !current.status.can_transition_to(&status) {
+            warn!(
+                "Ignored transition of task {} from {:?} to {:?}",
+                self.task.task_id, current.status, status
+            );
+            return false;
+        }
+
+        current.status = status.clone();
+        current.progress = progress;
+        if message.is_some() {
+            current.message = message.clone();
+        }
+
+        // Estimate the remaining time from the average speed since the start
+        let elapsed = current.started_instant.elapsed().as_secs_f32();
+        let eta_secs = match status {
+            TaskStatus::InProgress if progress > 0.01 && elapsed > 1.0 => {
+                Some((elapsed * (1.0 - progress) / progress).round() as u64)
+            }
+            _ => None,
+        };
+
         let payload = TaskProgressPayload {
-            task_id: self.task_id.clone(),
-            task_name: self.task_name.clone(),
+            task_id: self.task.task_id.clone(),
+            task_name: self.task.task_name.clone(),
             progress,
-            status,
+            status: status.clone(),
             message,
+            started_at: current.started_at,
+            updated_at: tasks::now(),
+            eta_secs,
         };
 
-        if let Err(e) = self.app_handle.emit(env::EVENT_TASK_PROGRESS, payload) {
+        if let Some(manager) = self.task.app_handle.try_state::<TaskManager>() {
+            manager.update(&payload);
+        }
+
+        if let Err(e) = self.task.app_handle.emit(env::EVENT_TASK_PROGRESS, payload) {
             error!("Failed to emit task progress event: {}", e);
         }
+
+        if status.is_terminal() {
+            self.finish(status, current.started_at, current.message.clone());
+        }
+
+        true
+    }
+
+    /// Release the slot of the task and record it in the history
+    fn finish(&self, outcome: TaskStatus, started_at: u64, message: Option<String>) {
+        if let Some(manager) = self.task.app_handle.try_state::<TaskManager>() {
+            manager.finish(&self.task.task_id);
+        }
+
+        let record = TaskRecord {
+            task_id: self.task.task_id.clone(),
+            task_name: self.task.task_name.clone(),
+            kind: self.task.kind.clone(),
+            project_id: self.task.project_id.clone(),
+            started_at,
+            ended_at: tasks::now(),
+            outcome,
+            message,
+        };
+        if let Err(e) = TaskRecord::record(&self.task.app_handle, record) {
+            error!("Failed to record task {} in the history: {}", self.task.task_id, e);
+        }
     }
 }
 
 impl Drop for TaskProgress {
     fn drop(&mut self) {
-        // Ensure task is marked as completed when dropped
-        if let Err(e) = self.app_handle.emit(env::EVENT_TASK_PROGRESS, TaskProgressPayload {
-            task_id: self.task_id.clone(),
-            task_name: self.task_name.clone(),
-            progress: 1.0,
-            status: TaskStatus::Completed,
-            message: None,
-        }) {
-            error!("Failed to emit task completion event on drop: {}", e);
-        }
-    }
-}
\ No newline at end of file
+        // Sub-tasks do not own the outcome of the task
+        if self.label.is_some() {
+            return;
+        }
+
+        let finished = self
+            .task
+            .current
+            .lock()
+            .map(|current| current.status.is_terminal())
+            .unwrap_or(true);
+
+        // A task dropped without outcome has been interrupted (e.g. an error returned early)
+        if !finished {
+            if self.is_cancelled() {
+                self.cancelled(None);
+            } else {
+                self.fail(Some("Task ended without reporting its outcome".to_string()));
+            }
+        }
+    }
+}
@@ -0,0 +1,592 @@
+use crate::env;
+use crate::misc::errors::Result;
+use crate::misc::errors::Verror::MessageError;
+use crate::misc::tasks::TaskRecord;
+use crate::projects::models::backup::BackupState;
+use crate::projects::models::project::Project;
+use crate::projects::models::session::ProjectSession;
+use crate::settings::models::settings::AppSettings;
+use log::info;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_store::StoreExt;
+use uuid::Uuid;
+
+/// # Store Schema
+/// The store file holds the version of its schema (`STORE_SCHEMA_VERSION_KEY`, 0 if
+/// missing). A store written by an older version of the application is upgraded on
+/// startup by the migrations it has not gone through, in order, after a copy of the
+/// store file has been made. The store is then checked: a store that cannot be
+/// migrated or read stops the startup, its content is never replaced by defaults.
+
+/// A migration upgrading the content of the store from the previous version
+struct Migration {
+    version: u32,              // Version of the store once migrated
+    description: &'static str, // Logged when the migration runs
+    migrate: fn(&mut Map<String, Value>) -> Result<()>,
+}
+
+/// Migrations of the store, in order. The last version is the version of the schema of this application.
+const MIGRATIONS: [Migration; 4] = [
+    Migration {
+        version: 1,
+        description: "Fill the fields added to the projects and their plugins",
+        migrate: fill_project_fields,
+    },
+    Migration {
+        version: 2,
+        description: "Give an identifier to each project",
+        migrate: assign_project_ids,
+    },
+    Migration {
+        version: 3,
+        description: "Fill the settings sections added to the application settings",
+        migrate: fill_settings_sections,
+    },
+    Migration {
+        version: 4,
+        description: "Key the data of the projects by their identifier",
+        migrate: key_data_by_project_id,
+    },
+];
+
+/// Version of the schema of the store written by this application
+pub fn current_version() -> u32 {
+    MIGRATIONS[MIGRATIONS.len() - 1].version
+}
+
+/// Upgrade the store to the current version and check that it can be read
+pub fn migrate_store(app_handle: &AppHandle) -> Result<()> {
+    let store = app_handle.store(env::STORE_FILE_NAME)?;
+    let mut content: Map<String, Value> = store.entries().into_iter().collect();
+
+    // A new store is created at the current version
+    if content.is_empty() {
+        store.set(env::STORE_SCHEMA_VERSION_KEY, current_version());
+        store.save()?;
+        return Ok(());
+    }
+
+    let version = schema_version(&content)?;
+    if version == current_version() {
+        return check_store(&content);
+    }
+
+    let backup_path = backup_store(app_handle, version, &content)?;
+    info!("Store backed up to {} before its migration", backup_path.display());
+    migrate_content(&mut content, version).map_err(|e| {
+        MessageError(format!("{} (the store is unchanged, a copy is at {})", e, backup_path.display()))
+    })?;
+
+    for (key, value) in content {
+        store.set(key, value);
+    }
+    store.save()?;
+    info!("Store migrated from version {} to {}", version, current_version());
+
+    Ok(())
+}
+
+/// Version of the schema of the content of a store (0 if it has none).
+/// A store written by a newer version of the application is refused.
+fn schema_version(content: &Map<String, Value>) -> Result<u32> {
+    let version = match content.get(env::STORE_SCHEMA_VERSION_KEY) {
+        None => 0,
+        Some(value) => value
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| MessageError(format!("Invalid store schema version: {}", value)))?,
+    };
+    if version > current_version() {
+        return Err(MessageError(format!(
+            "The store has been written by a newer version of the application (schema version {}, this version reads up to {})",
+            version,
+            current_version()
+        )));
+    }
+
+    Ok(version)
+}
+
+/// Run the migrations of the content of a store written at a version, and check that the result can be read
+fn migrate_content(content: &mut Map<String, Value>, version: u32) -> Result<()> {
+    for migration in MIGRATIONS.iter().filter(|migration| migration.version > version) {
+        info!("Migrating the store to version {}: {}", migration.version, migration.description);
+        (migration.migrate)(content).map_err(|e| {
+            MessageError(format!("Failed to migrate the store to version {}: {}", migration.version, e))
+        })?;
+    }
+    content.insert(env::STORE_SCHEMA_VERSION_KEY.to_string(), json!(current_version()));
+
+    check_store(content)
+}
+
+/// Copy the store file next to it, before a migration
+fn backup_store(app_handle: &AppHandle, version: u32, content: &Map<String, Value>) -> Result<PathBuf> {
+    let store_path = app_handle.path().resolve(env::STORE_FILE_NAME, BaseDirectory::AppData)?;
+    let backup_path = store_path.with_file_name(format!(
+        "{}.v{}-{}.bak",
+        env::STORE_FILE_NAME,
+        version,
+        chrono::Local::now().format("%Y%m%d%H%M%S")
+    ));
+
+    // The store may not have been saved to its file yet
+    if store_path.is_file() {
+        fs::copy(&store_path, &backup_path)?;
+    } else {
+        fs::write(&backup_path, serde_json::to_vec_pretty(content)?)?;
+    }
+
+    Ok(backup_path)
+}
+
+/// Check that every entry of the store can be read by this version of the application
+fn check_store(content: &Map<String, Value>) -> Result<()> {
+    check_entry::<Vec<Project>>(content, env::STORE_PROJECTS_KEY)?;
+    check_entry::<AppSettings>(content, env::STORE_SETTINGS_KEY)?;
+    check_entry::<Vec<ProjectSession>>(content, env::STORE_SESSIONS_KEY)?;
+    check_entry::<Vec<TaskRecord>>(content, env::STORE_TASK_HISTORY_KEY)?;
+    check_entry::<Vec<BackupState>>(content, env::STORE_BACKUP_STATES_KEY)?;
+
+    Ok(())
+}
+
+fn check_entry<T: DeserializeOwned>(content: &Map<String, Value>, key: &str) -> Result<()> {
+    match content.get(key) {
+        Some(value) => serde_json::from_value::<T>(value.clone())
+            .map(|_| ())
+            .map_err(|e| MessageError(format!("The store entry \"{}\" cannot be read: {}", key, e))),
+        None => Ok(()),
+    }
+}
+
+/// Objects of an array entry of the store (none if the entry is missing)
+fn entry_objects<'a>(
+    content: &'a mut Map<String, Value>,
+    key: &str,
+) -> Result<impl Iterator<Item = &'a mut Map<String, Value>>> {
+    let items = match content.get_mut(key) {
+        Some(Value::Array(items)) => items.as_mut_slice(),
+        Some(_) => return Err(MessageError(format!("The store entry \"{}\" is not a list", key))),
+        None => &mut [],
+    };
+    if items.iter().any(|item| !item.is_object()) {
+        return Err(MessageError(format!("The store entry \"{}\" holds an invalid item", key)));
+    }
+
+    Ok(items.iter_mut().filter_map(Value::as_object_mut))
+}
+
+/// Version 1: the projects of the first versions have no health fields, their plugins may miss their scan fields
+fn fill_project_fields(content: &mut Map<String, Value>) -> Result<()> {
+    for project in entry_objects(content, env::STORE_PROJECTS_KEY)? {
+        for (field, default) in [("missing", json!(false)), ("file_hashes", json!({}))] {
+            project.entry(field).or_insert(default);
+        }
+        // The usage is computed when the projects are read, the values stored before are stale
+        for field in ["is_open", "last_opened", "hours_this_week", "hours_this_month"] {
+            project.remove(field);
+        }
+
+        // The plugins are scanned with their project
+        let last_scan_date = project.get("last_scan_date").cloned().unwrap_or(json!(0));
+        let plugins = project.get_mut("plugins").and_then(Value::as_array_mut);
+        for plugin in plugins.into_iter().flatten().filter_map(Value::as_object_mut) {
+            for (field, default) in [
+                ("is_enabled", json!(true)),
+                ("is_in_project", json!(false)),
+                ("marketplace_url", Value::Null),
+                ("docs_url", Value::Null),
+                ("size_on_disk", Value::Null),
+                ("last_scan_date", last_scan_date.clone()),
+            ] {
+                plugin.entry(field).or_insert(default);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Version 2: the projects are identified by a UUID, unique among the tracked projects
+fn assign_project_ids(content: &mut Map<String, Value>) -> Result<()> {
+    let mut known_ids = HashSet::new();
+
+    for project in entry_objects(content, env::STORE_PROJECTS_KEY)? {
+        let id = project
+            .get("id")
+            .and_then(Value::as_str)
+            .filter(|id| !id.is_empty() && !known_ids.contains(*id))
+            .map(str::to_string)
+            .unwrap_or_else(|| {
+                let path = project.get("path").and_then(Value::as_str).unwrap_or_default();
+                let id = Project::new_id(Path::new(path));
+                if known_ids.contains(&id) {
+                    Uuid::new_v4().hyphenated().to_string()
+                } else {
+                    id
+                }
+            });
+
+        known_ids.insert(id.clone());
+        project.insert("id".to_string(), json!(id));
+    }
+
+    Ok(())
+}
+
+/// Version 3: the settings of the first versions have no launch profiles, tasks, backups and discovery sections
+fn fill_settings_sections(content: &mut Map<String, Value>) -> Result<()> {
+    let defaults = serde_json::to_value(AppSettings::default())?;
+    let settings = match content.get_mut(env::STORE_SETTINGS_KEY) {
+        Some(Value::Object(settings)) => settings,
+        Some(_) => return Err(MessageError("The settings are not an object".to_string())),
+        None => return Ok(()),
+    };
+
+    for section in ["launch_profiles", "tasks", "backups", "discovery"] {
+        settings
+            .entry(section)
+            .or_insert_with(|| defaults[section].clone());
+    }
+    if let Some(discovery) = settings.get_mut("discovery").and_then(Value::as_object_mut) {
+        discovery.entry("excluded_patterns").or_insert(json!([]));
+        discovery.entry("library_roots").or_insert(json!([]));
+    }
+
+    Ok(())
+}
+
+/// Version 4: the launch profiles, backup schedules and states, sessions and task history are keyed by the
+/// project identifier instead of the project path. The data of the projects that are not tracked anymore is dropped,
+/// the tasks keep their record without a project.
+fn key_data_by_project_id(content: &mut Map<String, Value>) -> Result<()> {
+    let project_ids: HashMap<String, String> = entry_objects(content, env::STORE_PROJECTS_KEY)?
+        .filter_map(|project| {
+            let path = project.get("path")?.as_str()?;
+            let id = project.get("id")?.as_str()?;
+            Some((path.to_string(), id.to_string()))
+        })
+        .collect();
+
+    if let Some(settings) = content.get_mut(env::STORE_SETTINGS_KEY) {
+        for (section, field) in [("launch_profiles", "project_profiles"), ("backups", "project_schedules")] {
+            let Some(entries) = settings.get_mut(section).and_then(|section| section.get_mut(field)) else {
+                continue;
+            };
+            let Value::Object(by_path) = entries.take() else {
+                return Err(MessageError(format!("The settings entry \"{}.{}\" is not a map", section, field)));
+            };
+            *entries = Value::Object(
+                by_path
+                    .into_iter()
+                    .filter_map(|(path, value)| Some((project_ids.get(&path)?.clone(), value)))
+                    .collect(),
+            );
+        }
+    }
+
+    for key in [env::STORE_BACKUP_STATES_KEY, env::STORE_SESSIONS_KEY] {
+        let mut keyed = Vec::new();
+        for mut item in entry_objects(content, key)?.map(std::mem::take) {
+            let Some(path) = item.remove("project_path") else {
+                return Err(MessageError(format!("The store entry \"{}\" holds an item without project", key)));
+            };
+            let Some(path) = path.as_str() else {
+                return Err(MessageError(format!("The store entry \"{}\" holds an invalid project path", key)));
+            };
+            if let Some(id) = project_ids.get(path) {
+                item.insert("project_id".to_string(), json!(id));
+                keyed.push(Value::Object(item));
+            }
+        }
+        if content.contains_key(key) {
+            content.insert(key.to_string(), Value::Array(keyed));
+        }
+    }
+
+    for record in entry_objects(content, env::STORE_TASK_HISTORY_KEY)? {
+        let project_id = record
+            .remove("project_path")
+            .and_then(|path| project_ids.get(path.as_str()?).cloned());
+        record.insert("project_id".to_string(), json!(project_id));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Settings written before the launch profiles, tasks, backups and discovery sections
+    fn first_settings() -> Value {
+        let mut settings = serde_json::to_value(AppSettings::default()).unwrap();
+        let sections = settings.as_object_mut().unwrap();
+        for section in ["launch_profiles", "tasks", "backups", "discovery"] {
+            sections.remove(section);
+        }
+        settings
+    }
+
+    /// Store written before the schema versions
+    fn first_store() -> Map<String, Value> {
+        let content = json!({
+            "projects": [
+                {
+                    "name": "ActionRPG",
+                    "description": "A third-person action RPG",
+                    "engine_association": { "Standard": "5.3" },
+                    "path": "C:/UnrealProjects/ActionRPG/ActionRPG.uproject",
+                    "has_cpp": false,
+                    "plugins": [
+                        { "name": "Niagara", "is_enabled": true },
+                        {
+                            "name": "Inventory",
+                            "is_enabled": false,
+                            "is_in_project": true,
+                            "marketplace_url": null,
+                            "docs_url": "https://docs.example.com/inventory",
+                            "size_on_disk": 1024,
+                            "last_scan_date": 1700000100
+                        }
+                    ],
+                    "size_on_disk": 2147483648u64,
+                    "last_scan_date": 1700000000
+                },
+                {
+                    "name": "Shooter",
+                    "description": "",
+                    "engine_association": "Custom",
+                    "path": "C:/UnrealProjects/Shooter/Shooter.uproject",
+                    "has_cpp": true,
+                    "plugins": [],
+                    "size_on_disk": 1024,
+                    "last_scan_date": 1700000000,
+                    "is_open": true,
+                    "last_opened": 1700000000,
+                    "hours_this_week": 3.5,
+                    "hours_this_month": 12.0
+                }
+            ],
+            "app_settings": first_settings(),
+            "project_sessions": [],
+            "task_history": [],
+            "backup_states": []
+        });
+        content.as_object().unwrap().clone()
+    }
+
+    fn migrated(mut content: Map<String, Value>) -> Result<Map<String, Value>> {
+        let version = schema_version(&content)?;
+        migrate_content(&mut content, version)?;
+        Ok(content)
+    }
+
+    fn project_ids(content: &Map<String, Value>) -> Vec<String> {
+        content[env::STORE_PROJECTS_KEY]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|project| project["id"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn orders_the_migrations() {
+        for (index, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version, index as u32 + 1);
+        }
+        assert_eq!(current_version(), MIGRATIONS.len() as u32);
+    }
+
+    #[test]
+    fn migrates_a_store_without_version() {
+        let content = first_store();
+        assert_eq!(schema_version(&content).unwrap(), 0);
+        // The store cannot be read as it is
+        assert!(check_store(&content).is_err());
+
+        let content = migrated(content).unwrap();
+        assert_eq!(content[env::STORE_SCHEMA_VERSION_KEY], json!(current_version()));
+
+        let projects: Vec<Project> = serde_json::from_value(content[env::STORE_PROJECTS_KEY].clone()).unwrap();
+        assert_eq!(projects.len(), 2);
+        assert!(projects.iter().all(|project| !project.missing && project.file_hashes.is_empty()));
+
+        // The plugins are filled, what they had is kept
+        let plugins = &projects[0].plugins;
+        assert!(plugins[0].is_enabled && !plugins[0].is_in_project);
+        assert_eq!(plugins[0].last_scan_date, 1700000000);
+        assert!(!plugins[1].is_enabled && plugins[1].is_in_project);
+        assert_eq!(plugins[1].size_on_disk, Some(1024));
+        assert_eq!(plugins[1].last_scan_date, 1700000100);
+
+        // The stale usage is not kept
+        let shooter = content[env::STORE_PROJECTS_KEY][1].as_object().unwrap();
+        assert!(["is_open", "last_opened", "hours_this_week", "hours_this_month"]
+            .iter()
+            .all(|field| !shooter.contains_key(*field)));
+
+        let ids = project_ids(&content);
+        assert!(ids.iter().all(|id| Uuid::parse_str(id).is_ok()));
+        assert_ne!(ids[0], ids[1]);
+
+        let settings: AppSettings = serde_json::from_value(content[env::STORE_SETTINGS_KEY].clone()).unwrap();
+        assert!(settings.discovery.library_roots.is_empty());
+        assert_eq!(content[env::STORE_SETTINGS_KEY]["compression"], first_settings()["compression"]);
+    }
+
+    #[test]
+    fn keeps_the_unique_project_ids() {
+        let mut content = first_store();
+        let projects = content[env::STORE_PROJECTS_KEY].as_array_mut().unwrap();
+        let mut copy = projects[0].clone();
+        copy["path"] = json!("C:/UnrealProjects/ActionRPG_Copy/ActionRPG.uproject");
+        projects.push(copy);
+        projects[0]["id"] = json!("3f6c1a52-8d2e-4b7a-9c41-5e0d2f8a6b13");
+        projects[1]["id"] = json!("");
+        projects[2]["id"] = json!("3f6c1a52-8d2e-4b7a-9c41-5e0d2f8a6b13");
+
+        let ids = project_ids(&migrated(content).unwrap());
+        assert_eq!(ids[0], "3f6c1a52-8d2e-4b7a-9c41-5e0d2f8a6b13");
+        assert!(!ids[1].is_empty() && ids[1] != ids[0]);
+        assert!(ids[2] != ids[0] && ids[2] != ids[1]);
+    }
+
+    #[test]
+    fn fills_the_settings_sections() {
+        let mut content = first_store();
+        let mut discovery = serde_json::to_value(AppSettings::default().discovery).unwrap();
+        discovery.as_object_mut().unwrap().remove("library_roots");
+        discovery.as_object_mut().unwrap().remove("excluded_patterns");
+        content.get_mut(env::STORE_SETTINGS_KEY).unwrap()["discovery"] = discovery;
+
+        let content = migrated(content).unwrap();
+        let settings = content[env::STORE_SETTINGS_KEY].as_object().unwrap();
+        assert!(["launch_profiles", "tasks", "backups", "discovery"]
+            .iter()
+            .all(|section| settings.contains_key(*section)));
+        assert_eq!(settings["discovery"]["library_roots"], json!([]));
+        assert_eq!(settings["discovery"]["excluded_patterns"], json!([]));
+    }
+
+    #[test]
+    fn keys_the_project_data_by_id() {
+        const ACTION_RPG: &str = "C:/UnrealProjects/ActionRPG/ActionRPG.uproject";
+        const REMOVED: &str = "C:/UnrealProjects/Removed/Removed.uproject";
+
+        let mut content = first_store();
+        let mut settings = serde_json::to_value(AppSettings::default()).unwrap();
+        settings["launch_profiles"]["project_profiles"] = json!({ ACTION_RPG: [], REMOVED: [] });
+        content.insert(env::STORE_SETTINGS_KEY.to_string(), settings);
+        let session = |path: &str| {
+            json!({
+                "project_path": path,
+                "kind": "Editor",
+                "started_at": 1700000000,
+                "ended_at": 1700003600,
+                "duration_secs": 3600,
+                "command_line": "UnrealEditor.exe",
+                "engine_version": "5.3"
+            })
+        };
+        content.insert(env::STORE_SESSIONS_KEY.to_string(), json!([session(ACTION_RPG), session(REMOVED)]));
+        content.insert(
+            env::STORE_BACKUP_STATES_KEY.to_string(),
+            json!([{
+                "project_path": ACTION_RPG,
+                "scheduled_since": 1700000000,
+                "last_run": null,
+                "retry_at": null,
+                "last_error": null,
+                "archives": []
+            }]),
+        );
+        let record = |path: Value| {
+            json!({
+                "task_id": "compress_project_1700000000000",
+                "task_name": "Compressing project: ActionRPG",
+                "kind": "Compress",
+                "project_path": path,
+                "started_at": 1700000000,
+                "ended_at": 1700000100,
+                "outcome": "Completed",
+                "message": null
+            })
+        };
+        content.insert(
+            env::STORE_TASK_HISTORY_KEY.to_string(),
+            json!([record(json!(ACTION_RPG)), record(json!(REMOVED)), record(Value::Null)]),
+        );
+
+        let content = migrated(content).unwrap();
+        let id = project_ids(&content)[0].clone();
+
+        let settings: AppSettings = serde_json::from_value(content[env::STORE_SETTINGS_KEY].clone()).unwrap();
+        assert_eq!(settings.launch_profiles.project_profiles.keys().collect::<Vec<_>>(), vec![&id]);
+        assert!(settings.backups.project_schedules.is_empty());
+
+        let sessions: Vec<ProjectSession> = serde_json::from_value(content[env::STORE_SESSIONS_KEY].clone()).unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].project_id, id);
+
+        let states: Vec<BackupState> = serde_json::from_value(content[env::STORE_BACKUP_STATES_KEY].clone()).unwrap();
+        assert_eq!(states[0].project_id, id);
+
+        let history: Vec<TaskRecord> = serde_json::from_value(content[env::STORE_TASK_HISTORY_KEY].clone()).unwrap();
+        let project_ids: Vec<Option<String>> = history.into_iter().map(|record| record.project_id).collect();
+        assert_eq!(project_ids, vec![Some(id), None, None]);
+    }
+
+    #[test]
+    fn migrates_a_store_without_settings() {
+        let mut content = first_store();
+        content.remove(env::STORE_SETTINGS_KEY);
+
+        let content = migrated(content).unwrap();
+        assert!(!content.contains_key(env::STORE_SETTINGS_KEY));
+    }
+
+    #[test]
+    fn reads_the_current_version() {
+        let content = migrated(first_store()).unwrap();
+        assert_eq!(schema_version(&content).unwrap(), current_version());
+        assert!(check_store(&content).is_ok());
+    }
+
+    #[test]
+    fn refuses_a_newer_or_invalid_version() {
+        for version in [json!(current_version() + 1), json!(-1), json!("3"), json!(1.5)] {
+            let mut content = first_store();
+            content.insert(env::STORE_SCHEMA_VERSION_KEY.to_string(), version.clone());
+            assert!(schema_version(&content).is_err(), "{}", version);
+        }
+    }
+
+    #[test]
+    fn refuses_unreadable_entries() {
+        // A project that cannot be read fails the migration instead of being dropped
+        let mut content = first_store();
+        content[env::STORE_PROJECTS_KEY][0].as_object_mut().unwrap().remove("name");
+        assert!(migrated(content).is_err());
+
+        let mut content = first_store();
+        content.insert(env::STORE_PROJECTS_KEY.to_string(), json!({}));
+        assert!(migrated(content).is_err());
+
+        let mut content = first_store();
+        content[env::STORE_PROJECTS_KEY].as_array_mut().unwrap().push(json!("ActionRPG"));
+        assert!(migrated(content).is_err());
+
+        let mut content = first_store();
+        content.insert(env::STORE_SESSIONS_KEY.to_string(), json!([{ "project_path": 3 }]));
+        assert!(migrated(content).is_err());
+    }
+}
@@ -0,0 +1,281 @@
+use crate::env;
+use crate::misc::errors::Verror::MessageError;
+use crate::misc::errors::{ErrorLevel, Result};
+use crate::misc::payloads::{TaskProgressPayload, TaskStatus};
+use crate::misc::prelude::log;
+use log::info;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{command, AppHandle, Manager, State};
+use tauri_plugin_store::StoreExt;
+use tokio::sync::futures::Notified;
+use tokio::sync::Notify;
+
+/// # Task Manager
+/// This module keeps track of the background tasks (see `TaskProgress`).
+/// Heavy tasks are queued and only start when the concurrency limit of their
+/// kind allows it (see `TaskSettings`). Each task gets a cancellation token
+/// the frontend can trigger by the task id. Cancellation is cooperative:
+/// the tasks check their token between steps and roll back or stop cleanly.
+/// Finished tasks are recorded in the store to keep a history.
+
+/// Kind of background task, used to apply the concurrency limits
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TaskKind {
+    Compress,   // Project archive creation
+    Extract,    // Project archive extraction
+    Verify,     // Integrity check of an archive
+    Clean,      // Deletion of the generated files of a project
+    Build,      // UnrealBuildTool compilation
+    Discovery,  // Search of projects on the disk
+    PluginScan, // Plugin analysis of the tracked projects
+    Other,      // Light tasks, never queued
+}
+
+/// A queued or running task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskInfo {
+    pub task_id: String,
+    pub task_name: String,
+    pub kind: TaskKind,
+    pub project_path: Option<String>, // Project the task works on (None if it is not project specific)
+    pub status: TaskStatus,
+    pub progress: f32,                // 0.0 to 1.0
+    pub message: Option<String>,      // Last message reported by the task
+    pub queued_at: u64,               // Creation date (seconds since UNIX epoch)
+    pub started_at: Option<u64>,      // Start date (None while the task is queued)
+    pub eta_secs: Option<u64>,        // Estimated remaining time (None if unknown)
+}
+
+/// A finished task, persisted in the store
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskRecord {
+    pub task_id: String,
+    pub task_name: String,
+    pub kind: TaskKind,
+    pub project_id: Option<String>, // Project the task worked on (see `Project::id`, None if it is not project specific)
+    pub started_at: u64,         // Start date (seconds since UNIX epoch)
+    pub ended_at: u64,           // End date (seconds since UNIX epoch)
+    pub outcome: TaskStatus,     // Completed, Failed or Cancelled
+    pub message: Option<String>, // Last message reported by the task
+}
+
+/// Cancellation flag shared between a running task and the task manager
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Request the cancellation of the task
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Check if the cancellation of the task has been requested
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// Queued and running tasks, in creation order
+#[derive(Default)]
+pub struct TaskManager {
+    tasks: Mutex<Vec<(TaskInfo, CancellationToken)>>,
+    released: Notify, // Notified when a task finishes or is cancelled, to wake the queued tasks
+    history: Mutex<()>, // Serializes the updates of the task history in the store
+}
+
+impl TaskManager {
+    /// Register a new task and create its cancellation token
+    pub fn register(&self, info: TaskInfo) -> CancellationToken {
+        let token = CancellationToken::default();
+        if let Ok(mut tasks) = self.tasks.lock() {
+            tasks.push((info, token.clone()));
+        }
+        token
+    }
+
+    /// Start a queued task if the limit of its kind allows it, returns true if the task is running.
+    /// Tasks of the same kind start in the order they have been queued.
+    pub fn try_start(&self, task_id: &str, concurrency_limit: Option<usize>) -> bool {
+        let Ok(mut tasks) = self.tasks.lock() else {
+            return false;
+        };
+        let Some(index) = tasks.iter().position(|(info, _)| info.task_id == task_id) else {
+            return false;
+        };
+
+        let kind = tasks[index].0.kind.clone();
+        let same_kind = || tasks.iter().filter(|(info, _)| info.kind == kind);
+        let running = same_kind().filter(|(info, _)| info.started_at.is_some()).count();
+        let first_queued = same_kind()
+            .find(|(info, _)| info.started_at.is_none())
+            .map(|(info, _)| info.task_id == task_id)
+            .unwrap_or(true);
+
+        if !first_queued || concurrency_limit.map(|limit| running >= limit).unwrap_or(false) {
+            return false;
+        }
+
+        tasks[index].0.started_at = Some(now());
+        true
+    }
+
+    /// Future resolved the next time a task finishes or is cancelled.
+    /// It must be created before checking the queue so that no release is missed.
+    pub fn released(&self) -> Notified<'_> {
+        self.released.notified()
+    }
+
+    /// Update the state of a task as reported by its progress events
+    pub fn update(&self, payload: &TaskProgressPayload) {
+        if let Ok(mut tasks) = self.tasks.lock() {
+            if let Some((info, _)) = tasks.iter_mut().find(|(info, _)| info.task_id == payload.task_id) {
+                info.status = payload.status.clone();
+                info.progress = payload.progress;
+                info.eta_secs = payload.eta_secs;
+                if payload.message.is_some() {
+                    info.message = payload.message.clone();
+                }
+            }
+        }
+    }
+
+    /// Forget a finished task and let the queued tasks start, returns the task
+    pub fn finish(&self, task_id: &str) -> Option<TaskInfo> {
+        let finished = match self.tasks.lock() {
+            Ok(mut tasks) => tasks
+                .iter()
+                .position(|(info, _)| info.task_id == task_id)
+                .map(|index| tasks.remove(index).0),
+            Err(_) => None,
+        };
+
+        self.released.notify_waiters();
+        finished
+    }
+
+    /// Request the cancellation of a task, returns false if the task is unknown
+    pub fn cancel(&self, task_id: &str) -> bool {
+        let found = match self.tasks.lock() {
+            Ok(tasks) => match tasks.iter().find(|(info, _)| info.task_id == task_id) {
+                Some((_, token)) => {
+                    token.cancel();
+                    true
+                }
+                None => false,
+            },
+            Err(_) => false,
+        };
+
+        // Wake the task if it is waiting in the queue
+        self.released.notify_waiters();
+        found
+    }
+
+    /// List the queued and running tasks
+    pub fn list(&self) -> Vec<TaskInfo> {
+        match self.tasks.lock() {
+            Ok(tasks) => tasks.iter().map(|(info, _)| info.clone()).collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+impl TaskRecord {
+    pub fn get_history(app_handle: &AppHandle) -> Result<Vec<TaskRecord>> {
+        let store = app_handle.store(env::STORE_FILE_NAME)?;
+
+        let history = serde_json::from_value::<Vec<TaskRecord>>(
+            store.get(env::STORE_TASK_HISTORY_KEY).unwrap_or(json!([])),
+        )?;
+
+        Ok(history)
+    }
+
+    /// Adds a finished task to the history, dropping the oldest records above the limit
+    pub fn record(app_handle: &AppHandle, record: TaskRecord) -> Result<()> {
+        let manager = app_handle.state::<TaskManager>();
+        let _history_guard = manager.history.lock().unwrap();
+        let mut history = Self::get_history(app_handle)?;
+        history.push(record);
+        if history.len() > env::TASK_HISTORY_MAX_RECORDS {
+            history.drain(..history.len() - env::TASK_HISTORY_MAX_RECORDS);
+        }
+
+        Self::save_history(app_handle, &history)
+    }
+
+    /// Moves the records of the tasks of a project to another project
+    pub fn move_to_project(app_handle: &AppHandle, from_id: &str, into_id: &str) -> Result<()> {
+        let manager = app_handle.state::<TaskManager>();
+        let _history_guard = manager.history.lock().unwrap();
+        let mut history = Self::get_history(app_handle)?;
+
+        let mut moved = false;
+        for record in history.iter_mut().filter(|record| record.project_id.as_deref() == Some(from_id)) {
+            record.project_id = Some(into_id.to_string());
+            moved = true;
+        }
+        if moved {
+            Self::save_history(app_handle, &history)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn save_history(app_handle: &AppHandle, history: &[TaskRecord]) -> Result<()> {
+        let store = app_handle.store(env::STORE_FILE_NAME)?;
+        store.set(env::STORE_TASK_HISTORY_KEY, serde_json::to_value(history)?);
+        store.save()?;
+
+        Ok(())
+    }
+}
+
+/// Current date in seconds since UNIX epoch
+pub fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Request the cancellation of a queued or running task
+#[command]
+pub fn cancel_task(app_handle: AppHandle, manager: State<'_, TaskManager>, task_id: String) -> Result<()> {
+    if !manager.cancel(&task_id) {
+        return Err(MessageError(format!("No running task with id {}", task_id)));
+    }
+
+    info!("Cancellation requested for task {}", task_id);
+    log(
+        &app_handle,
+        ErrorLevel::Info,
+        &format!("Cancelling task {}...", task_id),
+    );
+
+    Ok(())
+}
+
+/// List the queued and running tasks
+#[command]
+pub fn list_tasks(manager: State<'_, TaskManager>) -> Result<Vec<TaskInfo>> {
+    Ok(manager.list())
+}
+
+/// Get the finished tasks, most recent first
+#[command]
+pub fn get_task_history(app_handle: AppHandle, limit: Option<usize>) -> Result<Vec<TaskRecord>> {
+    let mut history = TaskRecord::get_history(&app_handle)?;
+    history.reverse();
+
+    if let Some(limit) = limit {
+        history.truncate(limit);
+    }
+
+    Ok(history)
+}
@@ -0,0 +1,584 @@
+use crate::env;
+use crate::misc::errors::Result;
+use crate::misc::errors::Verror::MessageError;
+use crate::misc::progress::TaskProgress;
+use crate::misc::tasks::CancellationToken;
+use crate::projects::actions::archive_volumes::{self, VolumeReader};
+use crate::projects::actions::project_compressor::CompressionAlgorithm;
+use crate::projects::models::archive::ArchiveManifest;
+use log::warn;
+use std::cell::Cell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::{Component, Path, PathBuf};
+use std::rc::Rc;
+use std::time::Instant;
+
+/// # Archive Reader
+/// This module reads the archives written by the archive writer, and more
+/// generally any zip, 7z or tar archive, split into volumes or not. Each entry is validated before anything
+/// is written: absolute paths, `..` components and links are refused, so an
+/// archive can never write outside of the destination directory. The files are
+/// extracted to a staging directory and only moved into the destination once the
+/// whole extraction has succeeded.
+
+/// Files written by an extraction
+#[derive(Debug, Clone, Default)]
+pub struct ExtractionSummary {
+    pub files: Vec<PathBuf>, // Extracted files, relative to the destination directory
+    pub size: u64,           // Total size of the extracted files in bytes
+}
+
+/// Guess the format of an archive from its file name (the volume number of a split archive is ignored)
+pub fn detect_algorithm(archive_path: &Path) -> Option<CompressionAlgorithm> {
+    let archive_path = archive_volumes::volume_base(archive_path).unwrap_or_else(|| archive_path.to_path_buf());
+    let file_name = archive_path.file_name()?.to_string_lossy().to_lowercase();
+
+    // Zip archives are read the same way whatever the compression of their entries
+    if file_name.ends_with(".zip") {
+        Some(CompressionAlgorithm::Zip)
+    } else if file_name.ends_with(".7z") {
+        Some(CompressionAlgorithm::SevenZip)
+    } else if file_name.ends_with(".tar.gz") || file_name.ends_with(".tgz") {
+        Some(CompressionAlgorithm::TarGz)
+    } else if file_name.ends_with(".tar.zst") || file_name.ends_with(".tzst") {
+        Some(CompressionAlgorithm::TarZst)
+    } else if file_name.ends_with(".tar") {
+        Some(CompressionAlgorithm::Tar)
+    } else {
+        None
+    }
+}
+
+/// Convert an archive entry name to a path relative to the destination directory.
+/// Returns None for the names that could escape it (absolute paths, drive letters, `..`).
+pub fn entry_path(name: &str) -> Option<PathBuf> {
+    let name = name.replace('\\', "/");
+    if name.starts_with('/') {
+        return None;
+    }
+
+    let mut path = PathBuf::new();
+    for part in name.split('/') {
+        match part {
+            "" | "." => continue,
+            ".." => return None,
+            part if part.contains(':') => return None,
+            part => path.push(part),
+        }
+    }
+
+    // Last line of defense against platform specific prefixes
+    if path.as_os_str().is_empty() || path.components().any(|component| !matches!(component, Component::Normal(_))) {
+        return None;
+    }
+
+    Some(path)
+}
+
+/// Read every entry of an archive in order. The visitor receives the name of the entry and its
+/// content (None for directories); links are skipped. The progress goes from 0.0 to 1.0 as the archive is read.
+/// The volumes of a split archive are joined transparently, the path can be any of them.
+pub fn read_archive(
+    archive_path: &Path,
+    progress: &TaskProgress,
+    mut visit: impl FnMut(&str, Option<&mut dyn Read>) -> Result<()>,
+) -> Result<()> {
+    let algorithm = detect_algorithm(archive_path)
+        .ok_or_else(|| MessageError(format!("Unsupported archive format: {}", archive_path.display())))?;
+
+    let reader = VolumeReader::open(archive_path)?;
+    let mut walker = ArchiveWalker {
+        progress,
+        last_report: Instant::now(),
+        position: Rc::new(Cell::new(0)),
+        archive_size: reader.size().max(1),
+        visit: &mut visit,
+    };
+    let cursor = ArchiveCursor {
+        inner: BufReader::with_capacity(env::ARCHIVE_BUFFER_SIZE, reader),
+        position: walker.position.clone(),
+        cancellation: progress.cancellation_token(),
+    };
+
+    match algorithm {
+        CompressionAlgorithm::Zip | CompressionAlgorithm::ZipZstd => walker.read_zip(cursor),
+        CompressionAlgorithm::SevenZip => walker.read_7z(cursor),
+        CompressionAlgorithm::Tar => walker.read_tar(cursor),
+        CompressionAlgorithm::TarGz => walker.read_tar(flate2::read::GzDecoder::new(cursor)),
+        CompressionAlgorithm::TarZst => walker.read_tar(zstd::Decoder::new(cursor)?),
+    }?;
+
+    walker.report("Archive read", true);
+    Ok(())
+}
+
+/// Read the manifest of an archive. The manifest written alongside the archive is used when there is one,
+/// otherwise the archive is read until its embedded manifest (the whole archive for the tar formats).
+pub fn read_manifest(archive_path: &Path, progress: &TaskProgress) -> Result<ArchiveManifest> {
+    let sidecar_path = ArchiveManifest::sidecar_path(archive_path);
+    if sidecar_path.is_file() {
+        return Ok(serde_json::from_slice(&fs::read(&sidecar_path)?)?);
+    }
+
+    let mut manifest = None;
+    read_archive(archive_path, progress, |name, data| {
+        if let (Some(data), true) = (data, name == env::ARCHIVE_MANIFEST_NAME) {
+            let mut content = Vec::new();
+            data.read_to_end(&mut content)?;
+            manifest = Some(serde_json::from_slice::<ArchiveManifest>(&content)?);
+        }
+        Ok(())
+    })?;
+
+    manifest.ok_or_else(|| {
+        MessageError(format!(
+            "{} has no manifest, it was not created by the project manager",
+            archive_path.display()
+        ))
+    })
+}
+
+/// Extract an archive to a directory. Existing files are only replaced if `overwrite` is set.
+/// The manifest of the archive is not extracted. On failure, the files of the destination are left as they were.
+pub fn extract_archive(
+    archive_path: &Path,
+    destination: &Path,
+    overwrite: bool,
+    progress: &TaskProgress,
+) -> Result<ExtractionSummary> {
+    let mut extraction = StagedExtraction::new(destination, overwrite)?;
+    extraction.extract(archive_path, progress)?;
+    extraction.commit()
+}
+
+/// An extraction written to a staging directory of its destination (see `ARCHIVE_STAGING_DIR`),
+/// whose files are moved into the destination once every archive has been extracted.
+/// The staging directory is removed when the extraction is dropped, committed or not.
+pub struct StagedExtraction {
+    destination: PathBuf,
+    staging: PathBuf,
+    replaced: PathBuf,              // Where the replaced files are moved aside while committing
+    keep_replaced: bool,            // The replaced files could not all be restored after a failed commit
+    overwrite: bool,                // Replace the files that already exist in the destination
+    files: BTreeMap<PathBuf, u64>,  // Extracted files relative to the destination, with their size
+    dirs: BTreeSet<PathBuf>,        // Directory entries relative to the destination
+}
+
+impl StagedExtraction {
+    pub fn new(destination: &Path, overwrite: bool) -> Result<Self> {
+        let staging = destination
+            .join(env::ARCHIVE_STAGING_DIR)
+            .join(chrono::Utc::now().timestamp_millis().to_string());
+        fs::create_dir_all(&staging)?;
+
+        Ok(Self {
+            destination: destination.to_path_buf(),
+            replaced: staging.with_extension("replaced"),
+            keep_replaced: false,
+            staging,
+            overwrite,
+            files: BTreeMap::new(),
+            dirs: BTreeSet::new(),
+        })
+    }
+
+    /// Directory the files are extracted to until the extraction is committed
+    pub fn staging_dir(&self) -> &Path {
+        &self.staging
+    }
+
+    /// Extract an archive. Its files replace the ones extracted from the previous archives.
+    pub fn extract(&mut self, archive_path: &Path, progress: &TaskProgress) -> Result<()> {
+        read_archive(archive_path, progress, |name, data| {
+            let Some(relative_path) = entry_path(name) else {
+                return Err(MessageError(format!(
+                    "The archive contains an unsafe entry \"{}\", extraction aborted",
+                    name
+                )));
+            };
+            let target = self.staging.join(&relative_path);
+
+            let Some(data) = data else {
+                fs::create_dir_all(target)?;
+                self.dirs.insert(relative_path);
+                return Ok(());
+            };
+            if name == env::ARCHIVE_MANIFEST_NAME {
+                return Ok(());
+            }
+
+            // Fails before extracting the whole archive, checked again when committing
+            self.check_destination(&relative_path)?;
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+
+            let mut file = File::create(&target)?;
+            let size = io::copy(data, &mut file)?;
+            self.files.insert(relative_path, size);
+            Ok(())
+        })
+    }
+
+    /// Remove a file extracted from a previous archive, if there is one
+    pub fn remove_file(&mut self, relative_path: &Path) -> Result<()> {
+        if self.files.remove(relative_path).is_some() {
+            fs::remove_file(self.staging.join(relative_path))?;
+        }
+        Ok(())
+    }
+
+    /// Move the extracted files into the destination. Nothing is moved if one of them cannot replace
+    /// an existing file, and the files already moved are taken back if moving another one fails.
+    pub fn commit(mut self) -> Result<ExtractionSummary> {
+        for relative_path in self.files.keys() {
+            self.check_destination(relative_path)?;
+        }
+
+        for relative_path in &self.dirs {
+            fs::create_dir_all(self.destination.join(relative_path))?;
+        }
+
+        let mut moved = Vec::new();
+        let mut replaced = Vec::new();
+        if let Err(e) = self.move_files(&mut moved, &mut replaced) {
+            let not_restored = self.restore(&moved, &replaced);
+            if not_restored.is_empty() {
+                return Err(e);
+            }
+
+            self.keep_replaced = true;
+            return Err(MessageError(format!(
+                "{}. The destination could not be restored, the replaced files are kept in {}: {}",
+                e,
+                self.replaced.display(),
+                not_restored
+                    .iter()
+                    .map(|path| path.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )));
+        }
+
+        Ok(ExtractionSummary {
+            files: self.files.keys().cloned().collect(),
+            size: self.files.values().sum(),
+        })
+    }
+
+    /// Move the extracted files into the destination, the files they replace being moved aside first
+    fn move_files(&self, moved: &mut Vec<PathBuf>, replaced: &mut Vec<PathBuf>) -> Result<()> {
+        for relative_path in self.files.keys() {
+            let target = self.destination.join(relative_path);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+
+            if target.is_file() {
+                let aside = self.replaced.join(relative_path);
+                if let Some(parent) = aside.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::rename(&target, &aside)?;
+                replaced.push(relative_path.clone());
+            }
+
+            // Same file system, the file is moved at once
+            fs::rename(self.staging.join(relative_path), &target)?;
+            moved.push(relative_path.clone());
+        }
+        Ok(())
+    }
+
+    /// Take back the files moved by a failed commit and put the replaced ones back,
+    /// returns the files of the destination that could not be restored
+    fn restore(&self, moved: &[PathBuf], replaced: &[PathBuf]) -> Vec<PathBuf> {
+        let mut not_restored = Vec::new();
+        for relative_path in moved {
+            if let Err(e) = fs::remove_file(self.destination.join(relative_path)) {
+                warn!("Failed to remove the extracted file {}: {}", relative_path.display(), e);
+                not_restored.push(relative_path.clone());
+            }
+        }
+        for relative_path in replaced {
+            if let Err(e) = fs::rename(self.replaced.join(relative_path), self.destination.join(relative_path)) {
+                warn!("Failed to restore the replaced file {}: {}", relative_path.display(), e);
+                not_restored.push(relative_path.clone());
+            }
+        }
+        not_restored
+    }
+
+    fn check_destination(&self, relative_path: &Path) -> Result<()> {
+        let target = self.destination.join(relative_path);
+        if !self.overwrite && target.exists() {
+            return Err(MessageError(format!(
+                "The file {} already exists in the destination directory",
+                target.display()
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Drop for StagedExtraction {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_dir_all(&self.staging) {
+            warn!("Failed to remove the staging directory {}: {}", self.staging.display(), e);
+        }
+        if !self.keep_replaced && self.replaced.exists() {
+            if let Err(e) = fs::remove_dir_all(&self.replaced) {
+                warn!("Failed to remove the replaced files {}: {}", self.replaced.display(), e);
+            }
+        }
+        // Other extractions to the same destination may be using it
+        if let Some(staging_root) = self.staging.parent() {
+            let _ = fs::remove_dir(staging_root);
+        }
+    }
+}
+
+/// Callback receiving each entry of an archive
+type EntryVisitor<'v> = dyn FnMut(&str, Option<&mut dyn Read>) -> Result<()> + 'v;
+
+/// State of an archive being read
+struct ArchiveWalker<'a, 'v> {
+    progress: &'a TaskProgress,
+    last_report: Instant,
+    position: Rc<Cell<u64>>, // Bytes of the archive read so far, updated by the cursor
+    archive_size: u64,
+    visit: &'v mut EntryVisitor<'v>,
+}
+
+impl ArchiveWalker<'_, '_> {
+    fn read_zip<R: Read + Seek>(&mut self, reader: R) -> Result<()> {
+        let mut archive = zip::ZipArchive::new(reader).map_err(zip_error)?;
+
+        for index in 0..archive.len() {
+            self.progress.check_cancelled()?;
+            let mut file = archive.by_index(index).map_err(zip_error)?;
+            let name = file.name().to_string();
+
+            if file.is_symlink() {
+                warn!("Skipped link entry {} of the archive", name);
+                continue;
+            }
+            if file.is_dir() {
+                (self.visit)(&name, None)?;
+            } else {
+                self.report(&name, false);
+                (self.visit)(&name, Some(&mut file))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn read_tar<R: Read>(&mut self, reader: R) -> Result<()> {
+        let mut archive = tar::Archive::new(reader);
+
+        for entry in archive.entries()? {
+            self.progress.check_cancelled()?;
+            let mut entry = entry?;
+            let name = entry.path()?.to_string_lossy().to_string();
+
+            match entry.header().entry_type() {
+                tar::EntryType::Directory => (self.visit)(&name, None)?,
+                tar::EntryType::Regular | tar::EntryType::Continuous => {
+                    self.report(&name, false);
+                    (self.visit)(&name, Some(&mut entry))?
+                }
+                // Links could point outside of the destination directory
+                entry_type => warn!("Skipped {:?} entry {} of the archive", entry_type, name),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn read_7z<R: Read + Seek>(&mut self, mut reader: R) -> Result<()> {
+        let archive_size = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(0))?;
+        let mut archive = sevenz_rust::SevenZReader::new(reader, archive_size, sevenz_rust::Password::empty())
+            .map_err(sevenz_error)?;
+
+        // The 7z reader only passes its own errors, the error of the visitor is kept aside
+        let mut visit_error = None;
+        let result = archive.for_each_entries(|entry, data| {
+            let name = entry.name().to_string();
+            let result = if entry.is_directory() {
+                (self.visit)(&name, None)
+            } else {
+                self.report(&name, false);
+                (self.visit)(&name, Some(&mut *data)).and_then(|_| {
+                    // The next entries of a solid block can only be read once this one is consumed
+                    io::copy(data, &mut io::sink())?;
+                    Ok(())
+                })
+            };
+
+            match result {
+                Ok(_) => Ok(true),
+                Err(e) => {
+                    visit_error = Some(e);
+                    Ok(false)
+                }
+            }
+        });
+
+        if let Some(e) = visit_error {
+            return Err(e);
+        }
+        result.map_err(sevenz_error)?;
+        self.progress.check_cancelled()
+    }
+
+    /// Report the progress, at most once per interval unless forced
+    fn report(&mut self, message: &str, force: bool) {
+        if !force && self.last_report.elapsed().as_millis() < env::ARCHIVE_PROGRESS_INTERVAL_MS as u128 {
+            return;
+        }
+        self.last_report = Instant::now();
+
+        let ratio = self.position.get() as f32 / self.archive_size as f32;
+        self.progress.update(ratio, Some(message.to_string()));
+    }
+}
+
+/// Reader of the archive file, tracking the position for the progress and stopping on cancellation
+struct ArchiveCursor {
+    inner: BufReader<VolumeReader>,
+    position: Rc<Cell<u64>>,
+    cancellation: CancellationToken,
+}
+
+impl Read for ArchiveCursor {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // Not `Interrupted`: the readers would retry the read
+        if self.cancellation.is_cancelled() {
+            return Err(io::Error::other("Archive extraction stopped after cancellation"));
+        }
+
+        let read = self.inner.read(buf)?;
+        self.position.set(self.position.get() + read as u64);
+        Ok(read)
+    }
+}
+
+impl Seek for ArchiveCursor {
+    fn seek(&mut self, position: SeekFrom) -> io::Result<u64> {
+        let position = self.inner.seek(position)?;
+        self.position.set(position);
+        Ok(position)
+    }
+}
+
+fn zip_error(error: zip::result::ZipError) -> crate::misc::errors::Verror {
+    MessageError(format!("Failed to read the zip archive: {}", error))
+}
+
+fn sevenz_error(error: sevenz_rust::Error) -> crate::misc::errors::Verror {
+    MessageError(format!("Failed to read the 7z archive: {}", error))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Staged extraction to a new destination holding `Config/Game.ini`, with the given files extracted
+    fn staged_extraction(name: &str, files: &[&str]) -> StagedExtraction {
+        let destination = std::env::temp_dir().join(format!("staged_extraction_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&destination);
+        fs::create_dir_all(destination.join("Config")).unwrap();
+        fs::write(destination.join("Config").join("Game.ini"), "old").unwrap();
+
+        let mut extraction = StagedExtraction::new(&destination, true).unwrap();
+        for file in files {
+            let staged = extraction.staging_dir().join(file);
+            fs::create_dir_all(staged.parent().unwrap()).unwrap();
+            fs::write(&staged, "new").unwrap();
+            extraction.files.insert(PathBuf::from(file), 3);
+        }
+        extraction
+    }
+
+    #[test]
+    fn commits_the_extracted_files() {
+        let extraction = staged_extraction("commit", &["Config/Game.ini", "Content/Map.umap"]);
+        let destination = extraction.destination.clone();
+
+        let summary = extraction.commit().unwrap();
+
+        assert_eq!(summary.files.len(), 2);
+        assert_eq!(fs::read_to_string(destination.join("Config").join("Game.ini")).unwrap(), "new");
+        assert_eq!(fs::read_to_string(destination.join("Content").join("Map.umap")).unwrap(), "new");
+        assert!(!destination.join(env::ARCHIVE_STAGING_DIR).exists());
+        fs::remove_dir_all(destination).unwrap();
+    }
+
+    #[test]
+    fn restores_the_destination_on_failure() {
+        let extraction = staged_extraction("rollback", &["Config/Game.ini", "Content/Map.umap", "Saved"]);
+        let destination = extraction.destination.clone();
+        // A directory cannot be replaced by a file, the last move fails
+        fs::create_dir_all(destination.join("Saved").join("Logs")).unwrap();
+
+        assert!(extraction.commit().is_err());
+
+        assert_eq!(fs::read_to_string(destination.join("Config").join("Game.ini")).unwrap(), "old");
+        assert!(!destination.join("Content").join("Map.umap").exists());
+        assert!(destination.join("Saved").join("Logs").is_dir());
+        assert!(!destination.join(env::ARCHIVE_STAGING_DIR).exists());
+        fs::remove_dir_all(destination).unwrap();
+    }
+
+    #[test]
+    fn keeps_relative_entries() {
+        assert_eq!(entry_path("MyGame/MyGame.uproject"), Some(PathBuf::from("MyGame").join("MyGame.uproject")));
+        assert_eq!(entry_path("./Config/DefaultEngine.ini"), Some(PathBuf::from("Config").join("DefaultEngine.ini")));
+        assert_eq!(entry_path("Content//Maps/"), Some(PathBuf::from("Content").join("Maps")));
+        assert_eq!(entry_path("Source/..MyGame/a..b.cpp"), Some(PathBuf::from("Source").join("..MyGame").join("a..b.cpp")));
+    }
+
+    #[test]
+    fn converts_backslashes() {
+        assert_eq!(entry_path(r"Source\MyGame\MyActor.cpp"), Some(PathBuf::from("Source").join("MyGame").join("MyActor.cpp")));
+        assert_eq!(entry_path(r"..\Windows\System32\evil.dll"), None);
+        assert_eq!(entry_path(r"Source\..\..\evil.dll"), None);
+    }
+
+    #[test]
+    fn refuses_parent_components() {
+        assert_eq!(entry_path(".."), None);
+        assert_eq!(entry_path("../evil.txt"), None);
+        assert_eq!(entry_path("MyGame/../../evil.txt"), None);
+        assert_eq!(entry_path("MyGame/Content/.."), None);
+    }
+
+    #[test]
+    fn refuses_absolute_paths() {
+        assert_eq!(entry_path("/etc/passwd"), None);
+        assert_eq!(entry_path("//server/share/evil.txt"), None);
+        assert_eq!(entry_path(r"\Windows\evil.dll"), None);
+        assert_eq!(entry_path(r"\\server\share\evil.txt"), None);
+    }
+
+    #[test]
+    fn refuses_drive_prefixes() {
+        assert_eq!(entry_path("C:evil.txt"), None);
+        assert_eq!(entry_path("C:/Windows/evil.dll"), None);
+        assert_eq!(entry_path(r"C:\Windows\evil.dll"), None);
+        assert_eq!(entry_path(r"MyGame\C:\evil.dll"), None);
+        assert_eq!(entry_path(r"\\?\C:\evil.dll"), None);
+    }
+
+    #[test]
+    fn refuses_empty_entries() {
+        assert_eq!(entry_path(""), None);
+        assert_eq!(entry_path("/"), None);
+        assert_eq!(entry_path("./"), None);
+    }
+}
@@ -0,0 +1,196 @@
+use crate::env;
+use crate::misc::errors::Verror::MessageError;
+use crate::misc::errors::{ErrorLevel, Result};
+use crate::misc::prelude::log;
+use crate::misc::progress::TaskProgress;
+use crate::misc::tasks::TaskKind;
+use crate::projects::actions::{archive_reader, archive_volumes};
+use crate::projects::models::archive::{ArchiveManifest, ManifestFile};
+use log::{error, warn};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tauri::{command, AppHandle};
+
+/// # Archive Verifier Actions
+/// This module checks that an archive is intact: every entry is read again and
+/// compared to the manifest embedded in the archive (or written next to it)
+/// when it has been created.
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveVerification {
+    pub archive_path: String,
+    pub valid: bool,                    // True if every file matches the manifest
+    pub project_name: String,
+    pub engine_association: String,
+    pub volume_count: usize,            // Number of files of the archive (1 if it is not split)
+    pub checked_files: usize,           // Files of the manifest found in the archive
+    pub missing_files: Vec<String>,     // Files of the manifest that are not in the archive
+    pub corrupted_files: Vec<String>,   // Files whose size or hash differs from the manifest
+    pub unexpected_files: Vec<String>,  // Files of the archive that are not in the manifest
+    pub duration_ms: u128,
+}
+
+/// Read an archive again and validate every file against its manifest
+#[command]
+pub async fn verify_archive(app_handle: AppHandle, archive_path: String) -> Result<ArchiveVerification> {
+    let start_time = std::time::Instant::now();
+    let archive_path = PathBuf::from(archive_path);
+
+    // Any volume of a split archive (or its path without the volume number) can be given
+    if archive_volumes::archive_files(&archive_path).is_empty() {
+        let error_msg = format!("Archive does not exist: {}", archive_path.display());
+        error!("{}", error_msg);
+        log(&app_handle, ErrorLevel::Error, &error_msg);
+        return Err(MessageError(error_msg));
+    }
+
+    let archive_name = archive_path
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("archive")
+        .to_string();
+
+    let task_id = format!("verify_archive_{}", chrono::Utc::now().timestamp_millis());
+    let progress = TaskProgress::queued(
+        app_handle.clone(),
+        task_id,
+        format!("Verifying archive: {}", archive_name),
+        TaskKind::Verify,
+        None,
+    )
+    .await?;
+
+    let verification = match check_archive(&archive_path, &progress) {
+        Ok(verification) => verification,
+        Err(_) if progress.is_cancelled() => {
+            progress.cancelled(Some(format!("Verification of {} cancelled", archive_name)));
+            return Err(progress.cancellation_error());
+        }
+        Err(e) => {
+            let error_msg = format!("Failed to verify {}: {}", archive_name, e);
+            log(&app_handle, ErrorLevel::Error, &error_msg);
+            progress.fail(Some(error_msg.clone()));
+            return Err(MessageError(error_msg));
+        }
+    };
+
+    let verification = ArchiveVerification {
+        duration_ms: start_time.elapsed().as_millis(),
+        ..verification
+    };
+
+    if verification.valid {
+        let success_msg = format!("{} is intact ({} files checked)", archive_name, verification.checked_files);
+        log(&app_handle, ErrorLevel::Info, &success_msg);
+        progress.complete(Some(success_msg));
+    } else {
+        // The verification itself succeeded, the archive is what failed it
+        let error_msg = format!(
+            "{} is damaged: {} missing, {} corrupted, {} unexpected file(s)",
+            archive_name,
+            verification.missing_files.len(),
+            verification.corrupted_files.len(),
+            verification.unexpected_files.len()
+        );
+        warn!("{}", error_msg);
+        log(&app_handle, ErrorLevel::Error, &error_msg);
+        progress.fail(Some(error_msg));
+    }
+
+    Ok(verification)
+}
+
+/// Hash every file of the archive and compare them to the manifest
+pub fn check_archive(archive_path: &Path, progress: &TaskProgress) -> Result<ArchiveVerification> {
+    let mut embedded_manifest = None;
+    let mut archived_files: HashMap<String, ManifestFile> = HashMap::new();
+
+    archive_reader::read_archive(archive_path, progress, |name, data| {
+        let Some(data) = data else {
+            return Ok(());
+        };
+
+        if name == env::ARCHIVE_MANIFEST_NAME {
+            let mut content = Vec::new();
+            data.read_to_end(&mut content)?;
+            embedded_manifest = Some(serde_json::from_slice::<ArchiveManifest>(&content)?);
+            return Ok(());
+        }
+
+        let (size, sha256) = hash_content(data)?;
+        archived_files.insert(
+            name.to_string(),
+            ManifestFile {
+                name: name.to_string(),
+                size,
+                sha256,
+                modified: 0, // Only the content is compared
+            },
+        );
+        Ok(())
+    })?;
+
+    // Archives created with the manifest only written alongside (or by another tool) rely on the sidecar file
+    let manifest = match embedded_manifest {
+        Some(manifest) => manifest,
+        None => {
+            let sidecar_path = ArchiveManifest::sidecar_path(archive_path);
+            let content = fs::read(&sidecar_path).map_err(|_| {
+                MessageError(format!(
+                    "The archive has no manifest to verify it against (looked for {} inside and {} next to it)",
+                    env::ARCHIVE_MANIFEST_NAME,
+                    sidecar_path.display()
+                ))
+            })?;
+            serde_json::from_slice::<ArchiveManifest>(&content)?
+        }
+    };
+
+    let mut missing_files = Vec::new();
+    let mut corrupted_files = Vec::new();
+    for expected in &manifest.files {
+        match archived_files.remove(&expected.name) {
+            Some(archived) if archived.size == expected.size && archived.sha256 == expected.sha256 => {}
+            Some(_) => corrupted_files.push(expected.name.clone()),
+            None => missing_files.push(expected.name.clone()),
+        }
+    }
+
+    let mut unexpected_files: Vec<String> = archived_files.into_keys().collect();
+    unexpected_files.sort();
+
+    Ok(ArchiveVerification {
+        archive_path: archive_path.display().to_string(),
+        valid: missing_files.is_empty() && corrupted_files.is_empty() && unexpected_files.is_empty(),
+        project_name: manifest.project_name,
+        engine_association: manifest.engine_association,
+        volume_count: archive_volumes::archive_files(archive_path).len(),
+        checked_files: manifest.files.len() - missing_files.len(),
+        missing_files,
+        corrupted_files,
+        unexpected_files,
+        duration_ms: 0,
+    })
+}
+
+/// Size and SHA-256 (lowercase hexadecimal) of a content
+fn hash_content(data: &mut dyn Read) -> Result<(u64, String)> {
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; env::ARCHIVE_BUFFER_SIZE];
+    let mut size = 0;
+
+    loop {
+        let read = data.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        size += read as u64;
+    }
+
+    Ok((size, format!("{:x}", hasher.finalize())))
+}
@@ -0,0 +1,220 @@
+use crate::env;
+use crate::misc::errors::Result;
+use crate::misc::errors::Verror::MessageError;
+use crate::misc::prelude::format_size;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// # Archive Volumes
+/// This module splits an archive into numbered volumes of a maximum size
+/// ("Project.zip.001", "Project.zip.002", ...) and joins them back. The volumes
+/// are plain slices of the archive, so the writers and readers of each format
+/// work on a volume set as if it was a single file, and the set can also be
+/// joined with any tool (e.g. `cat` or 7-Zip).
+
+/// Writer spreading an archive over numbered volumes. Seeking back into a previous volume is
+/// supported, as the zip and 7z writers update the headers of the entries they have written.
+pub struct VolumeWriter {
+    base_path: PathBuf,  // Path of the archive, without the volume number
+    volume_size: u64,
+    volumes: Vec<File>,
+    position: u64,
+    len: u64,            // Bytes written, including the ones written after seeking back
+}
+
+/// Reader joining the volumes of an archive (or reading a single archive file)
+pub struct VolumeReader {
+    volumes: Vec<File>,
+    starts: Vec<u64>,    // Position of the first byte of each volume in the joined archive
+    len: u64,
+    position: u64,
+}
+
+/// Path of a volume of an archive, numbered from 1
+pub fn volume_path(base_path: &Path, number: usize) -> PathBuf {
+    let mut file_name = base_path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(format!(".{:0width$}", number, width = env::ARCHIVE_VOLUME_DIGITS));
+    base_path.with_file_name(file_name)
+}
+
+/// Path of the archive a volume belongs to ("Project.zip.001" -> "Project.zip"), None if the path is not a volume
+pub fn volume_base(path: &Path) -> Option<PathBuf> {
+    let file_name = path.file_name()?.to_str()?;
+    let (base_name, number) = file_name.rsplit_once('.')?;
+
+    let is_volume_number = number.len() >= env::ARCHIVE_VOLUME_DIGITS
+        && number.chars().all(|c| c.is_ascii_digit())
+        && !base_name.is_empty();
+    is_volume_number.then(|| path.with_file_name(base_name))
+}
+
+/// Files of an archive, in order. The path can be a single archive, any volume of a set
+/// or the path of the archive without the volume number. Empty if nothing exists at this path.
+pub fn archive_files(path: &Path) -> Vec<PathBuf> {
+    let base_path = match volume_base(path) {
+        Some(base_path) if volume_path(&base_path, 1).is_file() => base_path,
+        _ if path.is_file() => return vec![path.to_path_buf()],
+        _ => path.to_path_buf(),
+    };
+
+    (1..)
+        .map(|number| volume_path(&base_path, number))
+        .take_while(|volume| volume.is_file())
+        .collect()
+}
+
+impl VolumeWriter {
+    pub fn create(base_path: &Path, volume_size: u64) -> Result<Self> {
+        if volume_size < env::ARCHIVE_MIN_VOLUME_SIZE {
+            return Err(MessageError(format!(
+                "The volume size must be at least {}",
+                format_size(env::ARCHIVE_MIN_VOLUME_SIZE)
+            )));
+        }
+
+        Ok(Self {
+            base_path: base_path.to_path_buf(),
+            volume_size,
+            volumes: Vec::new(),
+            position: 0,
+            len: 0,
+        })
+    }
+
+    /// Paths of the volumes written so far
+    pub fn paths(&self) -> Vec<PathBuf> {
+        (1..=self.volumes.len())
+            .map(|number| volume_path(&self.base_path, number))
+            .collect()
+    }
+
+    /// Get a volume by index, creating it and the ones before it if needed
+    fn volume(&mut self, index: usize) -> io::Result<&mut File> {
+        while self.volumes.len() <= index {
+            let path = volume_path(&self.base_path, self.volumes.len() + 1);
+            self.volumes.push(File::create(path)?);
+        }
+        Ok(&mut self.volumes[index])
+    }
+}
+
+impl Write for VolumeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        // A write never crosses the end of a volume, the caller writes the rest to the next one
+        let index = (self.position / self.volume_size) as usize;
+        let offset = self.position % self.volume_size;
+        let count = buf.len().min((self.volume_size - offset) as usize);
+
+        let file = self.volume(index)?;
+        file.seek(SeekFrom::Start(offset))?;
+        let written = file.write(&buf[..count])?;
+        self.position += written as u64;
+        self.len = self.len.max(self.position);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.volumes.iter_mut().try_for_each(|volume| volume.flush())
+    }
+}
+
+impl Seek for VolumeWriter {
+    fn seek(&mut self, position: SeekFrom) -> io::Result<u64> {
+        self.position = seek_position(position, self.position, self.len)?;
+        Ok(self.position)
+    }
+}
+
+impl VolumeReader {
+    /// Open an archive, see `archive_files` for the accepted paths
+    pub fn open(path: &Path) -> Result<Self> {
+        let paths = archive_files(path);
+        if paths.is_empty() {
+            return Err(MessageError(format!("Archive does not exist: {}", path.display())));
+        }
+
+        // A gap in the numbering would silently truncate the archive
+        if let Some(base_path) = paths.last().and_then(|last| volume_base(last)) {
+            let next_volume = volume_path(&base_path, paths.len() + 2);
+            if next_volume.exists() {
+                return Err(MessageError(format!(
+                    "The volume {} of the archive is missing",
+                    volume_path(&base_path, paths.len() + 1).display()
+                )));
+            }
+        }
+
+        let mut volumes = Vec::with_capacity(paths.len());
+        let mut starts = Vec::with_capacity(paths.len());
+        let mut len = 0;
+        for path in &paths {
+            starts.push(len);
+            len += fs::metadata(path)?.len();
+            volumes.push(File::open(path)?);
+        }
+
+        // Every volume but the last one is full, a smaller one has been truncated
+        let sizes: Vec<u64> = starts.windows(2).map(|pair| pair[1] - pair[0]).collect();
+        let volume_size = sizes.iter().copied().max().unwrap_or(0);
+        if let Some(index) = sizes.iter().position(|size| *size < volume_size) {
+            return Err(MessageError(format!(
+                "The volume {} of the archive is incomplete",
+                paths[index].display()
+            )));
+        }
+
+        Ok(Self {
+            volumes,
+            starts,
+            len,
+            position: 0,
+        })
+    }
+
+    /// Total size of the archive in bytes
+    pub fn size(&self) -> u64 {
+        self.len
+    }
+}
+
+impl Read for VolumeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() || self.position >= self.len {
+            return Ok(0);
+        }
+
+        // A read never crosses the end of a volume, the caller reads the rest from the next one
+        let index = self.starts.partition_point(|start| *start <= self.position) - 1;
+        let offset = self.position - self.starts[index];
+        let volume_end = self.starts.get(index + 1).copied().unwrap_or(self.len);
+        let count = buf.len().min((volume_end - self.position) as usize);
+
+        let file = &mut self.volumes[index];
+        file.seek(SeekFrom::Start(offset))?;
+        let read = file.read(&mut buf[..count])?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl Seek for VolumeReader {
+    fn seek(&mut self, position: SeekFrom) -> io::Result<u64> {
+        self.position = seek_position(position, self.position, self.len)?;
+        Ok(self.position)
+    }
+}
+
+/// Resolve a seek to an absolute position
+fn seek_position(position: SeekFrom, current: u64, len: u64) -> io::Result<u64> {
+    let target = match position {
+        SeekFrom::Start(offset) => Some(offset),
+        SeekFrom::End(offset) => len.checked_add_signed(offset),
+        SeekFrom::Current(offset) => current.checked_add_signed(offset),
+    };
+    target.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Invalid seek before the start of the archive"))
+}
@@ -0,0 +1,798 @@
+use crate::env;
+use crate::misc::errors::Result;
+use crate::misc::errors::Verror::MessageError;
+use crate::misc::progress::TaskProgress;
+use crate::misc::tasks::CancellationToken;
+use crate::projects::actions::archive_volumes::VolumeWriter;
+use crate::projects::actions::project_compressor::{CompressionAlgorithm, CompressionOptions};
+use crate::projects::models::archive::{ArchiveFilter, ArchiveManifest, ArchiveRules, ManifestFile};
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use ignore::WalkBuilder;
+use log::warn;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime};
+
+/// # Archive Writer
+/// This module writes the project archives in-process, without relying on the
+/// zip, tar or 7z binaries of the system. The files are selected by the archive
+/// rules of the request (see `ArchiveRules`) without touching the project.
+/// The directory is walked in a sorted order and the metadata that depends on
+/// the machine (owner, permissions, access dates) is normalized, so the same
+/// files always produce the same archive.
+/// The progress is reported per byte read through the task progress, and the
+/// files are hashed as they are read to build the manifest of the archive.
+/// The files of the zip archives are compressed in parallel, one file per thread,
+/// and added in order so the thread count does not change the archive.
+
+/// A file or directory to add to an archive
+#[derive(Debug, Clone)]
+pub struct ArchiveEntry {
+    pub source_path: PathBuf, // Absolute path of the file on the disk
+    pub name: String,         // Path inside the archive, relative and with '/' separators
+    pub is_dir: bool,
+    pub size: u64,            // Size in bytes (0 for directories)
+    pub modified: SystemTime, // Last modification date of the file
+}
+
+/// List the files and directories of a directory selected by the archive rules, sorted by path.
+/// Symbolic links are not followed and not archived.
+pub fn collect_entries(
+    source_dir: &Path,
+    rules: &ArchiveRules,
+    cancellation: &CancellationToken,
+) -> Result<Vec<ArchiveEntry>> {
+    let filter = Arc::new(ArchiveFilter::new(rules)?);
+
+    let mut walker = WalkBuilder::new(source_dir);
+    walker
+        .standard_filters(false)
+        .follow_links(false)
+        .sort_by_file_name(|a, b| a.cmp(b));
+    if rules.use_ignore_files {
+        // Honored even if the project is not a git repository (e.g. a Perforce workspace)
+        walker
+            .git_ignore(true)
+            .require_git(false)
+            .add_custom_ignore_filename(".p4ignore");
+    }
+
+    // Excluded directories are not walked at all
+    let root = source_dir.to_path_buf();
+    let walk_filter = filter.clone();
+    walker.filter_entry(move |entry| match entry.path().strip_prefix(&root) {
+        Ok(relative_path) if entry.depth() > 0 => {
+            let is_dir = entry.file_type().map(|file_type| file_type.is_dir()).unwrap_or(false);
+            !walk_filter.is_excluded(&archive_name(relative_path), is_dir)
+        }
+        _ => true,
+    });
+
+    let mut entries = Vec::new();
+    for entry in walker.build() {
+        if cancellation.is_cancelled() {
+            return Err(MessageError("Archive listing stopped after cancellation".to_string()));
+        }
+
+        let entry = entry.map_err(|e| MessageError(format!("Failed to list the project files: {}", e)))?;
+        let Some(file_type) = entry.file_type() else {
+            continue;
+        };
+        if entry.depth() == 0 || file_type.is_symlink() {
+            continue;
+        }
+
+        let Ok(relative_path) = entry.path().strip_prefix(source_dir) else {
+            continue;
+        };
+        let name = archive_name(relative_path);
+        // A manifest left by a previous extraction would be confused with the one of the archive
+        if name == env::ARCHIVE_MANIFEST_NAME || !filter.is_included(&name, file_type.is_dir()) {
+            continue;
+        }
+
+        let metadata = entry.metadata().map_err(|e| MessageError(e.to_string()))?;
+        entries.push(ArchiveEntry {
+            source_path: entry.path().to_path_buf(),
+            name,
+            is_dir: metadata.is_dir(),
+            size: if metadata.is_dir() { 0 } else { metadata.len() },
+            modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Files of a differential backup on top of a parent archive
+pub struct ArchiveDiff {
+    pub entries: Vec<ArchiveEntry>,     // Added and changed files to archive, with every directory
+    pub unchanged: Vec<ManifestFile>,   // Files of the parent snapshot with the same size and date
+    pub deleted: Vec<String>,           // Files of the parent snapshot that are not listed anymore
+}
+
+/// Compare the listed entries with the snapshot of a parent archive. A file is considered unchanged
+/// when its size and modification date are the ones recorded in the snapshot, it is not read again.
+pub fn diff_entries(entries: Vec<ArchiveEntry>, parent: &ArchiveManifest) -> ArchiveDiff {
+    let mut snapshot: HashMap<String, ManifestFile> = parent
+        .snapshot()
+        .into_iter()
+        .map(|file| (file.name.clone(), file))
+        .collect();
+
+    let mut diff = ArchiveDiff {
+        entries: Vec::new(),
+        unchanged: Vec::new(),
+        deleted: Vec::new(),
+    };
+    for entry in entries {
+        // Directories are cheap and keep the empty ones of the project
+        if entry.is_dir {
+            diff.entries.push(entry);
+            continue;
+        }
+
+        match snapshot.remove(&entry.name) {
+            Some(file) if file.size == entry.size && file.modified == unix_millis(entry.modified) => {
+                diff.unchanged.push(file)
+            }
+            _ => diff.entries.push(entry),
+        }
+    }
+
+    diff.deleted = snapshot.into_keys().collect();
+    diff.deleted.sort();
+    diff
+}
+
+/// Convert a relative path to an archive entry name ('/' separators on every platform)
+pub fn archive_name(relative_path: &Path) -> String {
+    relative_path
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Write the entries to an archive in the format of the algorithm, followed by the manifest
+/// completed with the hashes of the files. The progress goes from 0.0 to 1.0 as the bytes of the files are read.
+/// With a maximum volume size, the archive is split into numbered volumes next to the output path
+/// (see `archive_volumes`). Returns the files written; nothing is left behind on failure.
+/// The options are expected to be resolved for the algorithm (see `CompressionOptions::resolve`).
+pub fn write_archive(
+    entries: &[ArchiveEntry],
+    output_path: &Path,
+    algorithm: &CompressionAlgorithm,
+    options: &CompressionOptions,
+    max_volume_size: Option<u64>,
+    manifest: &mut ArchiveManifest,
+    progress: &TaskProgress,
+) -> Result<Vec<PathBuf>> {
+    let mut tracker = ArchiveTracker::new(entries, progress);
+
+    let (result, written_paths) = match max_volume_size {
+        Some(volume_size) => {
+            let mut volumes = VolumeWriter::create(output_path, volume_size)?;
+            let result = write_format(&mut volumes, entries, algorithm, options, manifest, &mut tracker);
+            (result, volumes.paths())
+        }
+        None => {
+            let result = File::create(output_path)
+                .map_err(Into::into)
+                .and_then(|file| write_format(file, entries, algorithm, options, manifest, &mut tracker));
+            (result, vec![output_path.to_path_buf()])
+        }
+    };
+
+    if let Err(e) = result {
+        // Never leave a half-written archive behind
+        for path in written_paths.iter().filter(|path| path.exists()) {
+            if let Err(e) = fs::remove_file(path) {
+                warn!("Failed to remove partial archive {}: {}", path.display(), e);
+            }
+        }
+        return Err(e);
+    }
+
+    tracker.report(true);
+    Ok(written_paths)
+}
+
+/// Write the archive in the format of the algorithm
+fn write_format<W: Write + Seek>(
+    file: W,
+    entries: &[ArchiveEntry],
+    algorithm: &CompressionAlgorithm,
+    options: &CompressionOptions,
+    manifest: &mut ArchiveManifest,
+    tracker: &mut ArchiveTracker,
+) -> Result<()> {
+    let threads = options.threads.unwrap_or(1).max(1);
+    let zip_settings = |method: zip::CompressionMethod, default_level: i32| match options.store_only {
+        true => ZipSettings {
+            method: zip::CompressionMethod::Stored,
+            level: None,
+            threads,
+        },
+        false => ZipSettings {
+            method,
+            level: Some(options.level.unwrap_or(default_level) as i64),
+            threads,
+        },
+    };
+
+    match algorithm {
+        CompressionAlgorithm::Zip => write_zip(
+            file,
+            entries,
+            zip_settings(zip::CompressionMethod::Deflated, env::ARCHIVE_DEFLATE_LEVEL),
+            manifest,
+            tracker,
+        ),
+        CompressionAlgorithm::ZipZstd => write_zip(
+            file,
+            entries,
+            zip_settings(zip::CompressionMethod::Zstd, env::ARCHIVE_ZSTD_LEVEL),
+            manifest,
+            tracker,
+        ),
+        CompressionAlgorithm::SevenZip => {
+            let preset = options.level.unwrap_or(env::ARCHIVE_LZMA_PRESET) as u32;
+            write_7z(file, entries, preset, manifest, tracker)
+        }
+        CompressionAlgorithm::Tar => {
+            write_tar(BufWriter::new(file), entries, manifest, tracker)?.flush()?;
+            Ok(())
+        }
+        CompressionAlgorithm::TarGz => {
+            // The gzip header has no file name nor date, so it does not change between runs
+            let level = flate2::Compression::new(options.level.unwrap_or(env::ARCHIVE_DEFLATE_LEVEL) as u32);
+            let encoder = flate2::write::GzEncoder::new(BufWriter::new(file), level);
+            write_tar(encoder, entries, manifest, tracker)?.finish()?.flush()?;
+            Ok(())
+        }
+        CompressionAlgorithm::TarZst => {
+            let level = options.level.unwrap_or(env::ARCHIVE_ZSTD_LEVEL);
+            let mut encoder = zstd::Encoder::new(BufWriter::new(file), level)?;
+            // Always in multithreaded mode: its output does not depend on the number of threads
+            encoder.multithread(threads as u32)?;
+            write_tar(encoder, entries, manifest, tracker)?.finish()?.flush()?;
+            Ok(())
+        }
+    }
+}
+
+/// Compression of the files of a zip archive
+#[derive(Debug, Clone, Copy)]
+struct ZipSettings {
+    method: zip::CompressionMethod,
+    level: Option<i64>, // None for the stored files
+    threads: usize,     // Files compressed at the same time
+}
+
+/// A file compressed by a worker thread, waiting to be added to the zip archive
+struct CompressedEntry {
+    data: Vec<u8>, // Zip archive holding the compressed file only
+    size: u64,
+    sha256: String,
+}
+
+fn write_zip<W: Write + Seek>(
+    file: W,
+    entries: &[ArchiveEntry],
+    settings: ZipSettings,
+    manifest: &mut ArchiveManifest,
+    tracker: &mut ArchiveTracker,
+) -> Result<()> {
+    let mut writer = zip::ZipWriter::new(BufWriter::new(file));
+
+    let mut start = 0;
+    while start < entries.len() {
+        tracker.check_cancelled()?;
+
+        // Big files would not fit in memory, they are streamed to the archive
+        let entry = &entries[start];
+        if !entry.is_dir && entry.size > env::ARCHIVE_PARALLEL_MAX_FILE_SIZE {
+            writer
+                .start_file(entry.name.as_str(), zip_options(entry, settings))
+                .map_err(zip_error)?;
+            let mut reader = tracker.open(entry)?;
+            io::copy(&mut reader, &mut writer)?;
+            tracker.finish_file(entry);
+            start += 1;
+            continue;
+        }
+
+        // The following small files are compressed in parallel, then copied to the archive in order
+        let mut end = start;
+        let mut batch_size = 0;
+        while let Some(entry) = entries.get(end) {
+            if !entry.is_dir && entry.size > env::ARCHIVE_PARALLEL_MAX_FILE_SIZE {
+                break;
+            }
+            if end > start && batch_size + entry.size > env::ARCHIVE_PARALLEL_BATCH_SIZE {
+                break;
+            }
+            batch_size += entry.size;
+            end += 1;
+        }
+
+        let batch = &entries[start..end];
+        for (entry, compressed) in batch.iter().zip(compress_batch(batch, settings, tracker)?) {
+            let Some(compressed) = compressed else {
+                writer
+                    .add_directory(format!("{}/", entry.name), zip_options(entry, settings))
+                    .map_err(zip_error)?;
+                continue;
+            };
+
+            let mut archive = zip::ZipArchive::new(io::Cursor::new(compressed.data)).map_err(zip_error)?;
+            writer
+                .raw_copy_file(archive.by_index_raw(0).map_err(zip_error)?)
+                .map_err(zip_error)?;
+            tracker.record_file(entry, compressed.size, compressed.sha256);
+        }
+        start = end;
+    }
+
+    let manifest_data = tracker.finish_manifest(manifest)?;
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(settings.method)
+        .compression_level(settings.level)
+        .unix_permissions(0o644);
+    writer
+        .start_file(env::ARCHIVE_MANIFEST_NAME, options)
+        .map_err(zip_error)?;
+    writer.write_all(&manifest_data)?;
+
+    writer.finish().map_err(zip_error)?.flush()?;
+    Ok(())
+}
+
+/// Options of a zip entry. Only depend on the file, so the same files always produce the same archive.
+fn zip_options(entry: &ArchiveEntry, settings: ZipSettings) -> zip::write::SimpleFileOptions {
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(settings.method)
+        .last_modified_time(zip_date_time(entry.modified))
+        .unix_permissions(if entry.is_dir { 0o755 } else { 0o644 })
+        .large_file(entry.size > u32::MAX as u64);
+
+    if entry.is_dir {
+        options
+    } else {
+        options.compression_level(settings.level)
+    }
+}
+
+/// Compress the files of a batch on the worker threads. Returns them in the order of the batch (None for the directories).
+fn compress_batch(
+    batch: &[ArchiveEntry],
+    settings: ZipSettings,
+    tracker: &mut ArchiveTracker,
+) -> Result<Vec<Option<CompressedEntry>>> {
+    let next_index = AtomicUsize::new(0);
+    // The errors are kept as messages, the errors of the crate cannot be sent between threads
+    let results: Vec<Mutex<Option<std::result::Result<CompressedEntry, String>>>> =
+        batch.iter().map(|_| Mutex::new(None)).collect();
+    let workers = settings
+        .threads
+        .min(batch.iter().filter(|entry| !entry.is_dir).count())
+        .max(1);
+    let tracker = Mutex::new(tracker);
+
+    std::thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let index = next_index.fetch_add(1, Ordering::Relaxed);
+                let Some(entry) = batch.get(index) else {
+                    break;
+                };
+                if entry.is_dir {
+                    continue;
+                }
+
+                let result = compress_entry(entry, settings, &tracker).map_err(|e| e.to_string());
+                let failed = result.is_err();
+                *results[index].lock().unwrap() = Some(result);
+                // The other files are not needed anymore, the archive has failed
+                if failed {
+                    next_index.store(batch.len(), Ordering::Relaxed);
+                    break;
+                }
+            });
+        }
+    });
+
+    batch
+        .iter()
+        .zip(results)
+        .map(|(entry, result)| match result.into_inner().unwrap() {
+            Some(result) => result.map(Some).map_err(MessageError),
+            None if entry.is_dir => Ok(None),
+            None => Err(MessageError(format!("{} has not been compressed", entry.name))),
+        })
+        .collect()
+}
+
+/// Compress a file to a zip archive in memory, hashing it as it is read
+fn compress_entry(
+    entry: &ArchiveEntry,
+    settings: ZipSettings,
+    tracker: &Mutex<&mut ArchiveTracker>,
+) -> Result<CompressedEntry> {
+    let file = File::open(&entry.source_path)
+        .map_err(|e| MessageError(format!("Failed to read {}: {}", entry.source_path.display(), e)))?;
+    let cancellation = {
+        let mut tracker = tracker.lock().unwrap();
+        tracker.start_file(entry);
+        tracker.cancellation.clone()
+    };
+
+    let mut reader = SharedReader {
+        inner: io::BufReader::with_capacity(env::ARCHIVE_BUFFER_SIZE, file),
+        tracker,
+        cancellation,
+        hasher: Sha256::new(),
+        size: 0,
+    };
+    let mut writer = zip::ZipWriter::new(io::Cursor::new(Vec::with_capacity(entry.size as usize)));
+    writer
+        .start_file(entry.name.as_str(), zip_options(entry, settings))
+        .map_err(zip_error)?;
+    io::copy(&mut reader, &mut writer)?;
+    let data = writer.finish().map_err(zip_error)?.into_inner();
+
+    Ok(CompressedEntry {
+        data,
+        size: reader.size,
+        sha256: format!("{:x}", reader.hasher.finalize()),
+    })
+}
+
+fn write_tar<W: Write>(
+    writer: W,
+    entries: &[ArchiveEntry],
+    manifest: &mut ArchiveManifest,
+    tracker: &mut ArchiveTracker,
+) -> Result<W> {
+    let mut builder = tar::Builder::new(writer);
+
+    for entry in entries {
+        tracker.check_cancelled()?;
+
+        // No owner nor group: the archive does not depend on the user who created it
+        let mut header = tar::Header::new_gnu();
+        header.set_mtime(unix_time(entry.modified));
+        header.set_uid(0);
+        header.set_gid(0);
+
+        if entry.is_dir {
+            header.set_entry_type(tar::EntryType::Directory);
+            header.set_mode(0o755);
+            header.set_size(0);
+            builder.append_data(&mut header, format!("{}/", entry.name), io::empty())?;
+            continue;
+        }
+
+        header.set_entry_type(tar::EntryType::Regular);
+        header.set_mode(0o644);
+        header.set_size(entry.size);
+        let reader = tracker.open(entry)?;
+        // The header holds the size of the file when it was listed: a file that grew since is cut,
+        // and one that shrank fails the archive instead of being padded
+        let reader = ExactReader {
+            inner: reader,
+            name: &entry.name,
+            remaining: entry.size,
+        };
+        builder.append_data(&mut header, &entry.name, reader)?;
+        tracker.finish_file(entry);
+    }
+
+    let manifest_data = tracker.finish_manifest(manifest)?;
+    let mut header = tar::Header::new_gnu();
+    header.set_entry_type(tar::EntryType::Regular);
+    header.set_mode(0o644);
+    header.set_size(manifest_data.len() as u64);
+    builder.append_data(&mut header, env::ARCHIVE_MANIFEST_NAME, manifest_data.as_slice())?;
+
+    Ok(builder.into_inner()?)
+}
+
+fn write_7z<W: Write + Seek>(
+    file: W,
+    entries: &[ArchiveEntry],
+    preset: u32,
+    manifest: &mut ArchiveManifest,
+    tracker: &mut ArchiveTracker,
+) -> Result<()> {
+    let mut writer = sevenz_rust::SevenZWriter::new(BufWriter::new(file)).map_err(sevenz_error)?;
+    writer.set_content_methods(vec![sevenz_rust::lzma::LZMA2Options::with_preset(preset).into()]);
+
+    for entry in entries {
+        tracker.check_cancelled()?;
+
+        let mut archive_entry = sevenz_rust::SevenZArchiveEntry::new();
+        archive_entry.name = entry.name.clone();
+        archive_entry.is_directory = entry.is_dir;
+        archive_entry.has_stream = !entry.is_dir;
+        if let Ok(modified) = sevenz_rust::nt_time::FileTime::try_from(entry.modified) {
+            archive_entry.last_modified_date = modified;
+            archive_entry.has_last_modified_date = true;
+        }
+
+        if entry.is_dir {
+            writer
+                .push_archive_entry::<File>(archive_entry, None)
+                .map_err(sevenz_error)?;
+            continue;
+        }
+
+        let reader = tracker.open(entry)?;
+        writer
+            .push_archive_entry(archive_entry, Some(reader))
+            .map_err(sevenz_error)?;
+        tracker.finish_file(entry);
+    }
+
+    let manifest_data = tracker.finish_manifest(manifest)?;
+    let mut manifest_entry = sevenz_rust::SevenZArchiveEntry::new();
+    manifest_entry.name = env::ARCHIVE_MANIFEST_NAME.to_string();
+    manifest_entry.has_stream = true;
+    writer
+        .push_archive_entry(manifest_entry, Some(manifest_data.as_slice()))
+        .map_err(sevenz_error)?;
+
+    writer.finish()?.flush()?;
+    Ok(())
+}
+
+/// Progress of an archive being written, shared by the readers of its files
+struct ArchiveTracker<'a> {
+    progress: &'a TaskProgress,
+    cancellation: CancellationToken,
+    total_bytes: u64,
+    total_files: usize,
+    processed_bytes: u64,
+    processed_files: usize,
+    current_file: String,
+    last_report: Instant,
+    hasher: Sha256,           // Hash of the file being read
+    file_size: u64,           // Bytes read from the file being read
+    files: Vec<ManifestFile>, // Files written, with their hash
+}
+
+impl<'a> ArchiveTracker<'a> {
+    fn new(entries: &[ArchiveEntry], progress: &'a TaskProgress) -> Self {
+        Self {
+            progress,
+            cancellation: progress.cancellation_token(),
+            total_bytes: entries.iter().map(|entry| entry.size).sum(),
+            total_files: entries.iter().filter(|entry| !entry.is_dir).count(),
+            processed_bytes: 0,
+            processed_files: 0,
+            current_file: String::new(),
+            last_report: Instant::now(),
+            hasher: Sha256::new(),
+            file_size: 0,
+            files: Vec::new(),
+        }
+    }
+
+    fn check_cancelled(&self) -> Result<()> {
+        self.progress.check_cancelled()
+    }
+
+    /// Open a file of the archive, its reads are counted in the progress
+    fn open(&mut self, entry: &ArchiveEntry) -> Result<TrackedReader<'_, 'a>> {
+        let file = File::open(&entry.source_path)
+            .map_err(|e| MessageError(format!("Failed to read {}: {}", entry.source_path.display(), e)))?;
+
+        self.start_file(entry);
+        self.hasher = Sha256::new();
+        self.file_size = 0;
+
+        Ok(TrackedReader {
+            inner: io::BufReader::with_capacity(env::ARCHIVE_BUFFER_SIZE, file),
+            tracker: self,
+        })
+    }
+
+    /// Count a file starting to be read in the progress
+    fn start_file(&mut self, entry: &ArchiveEntry) {
+        self.processed_files += 1;
+        self.current_file = entry.name.clone();
+        self.report(false);
+    }
+
+    /// Count bytes read in the progress
+    fn advance(&mut self, read: u64) {
+        self.processed_bytes += read;
+        self.report(false);
+    }
+
+    /// Add the file that has just been read to the manifest
+    fn finish_file(&mut self, entry: &ArchiveEntry) {
+        let hasher = std::mem::take(&mut self.hasher);
+        self.record_file(entry, self.file_size, format!("{:x}", hasher.finalize()));
+    }
+
+    /// Add a file to the manifest, the files must be recorded in the order of the archive
+    fn record_file(&mut self, entry: &ArchiveEntry, size: u64, sha256: String) {
+        self.files.push(ManifestFile {
+            name: entry.name.clone(),
+            size,
+            sha256,
+            modified: unix_millis(entry.modified),
+        });
+    }
+
+    /// Complete the manifest with the files written and serialize it
+    fn finish_manifest(&mut self, manifest: &mut ArchiveManifest) -> Result<Vec<u8>> {
+        manifest.files = std::mem::take(&mut self.files);
+        manifest.total_size = manifest.files.iter().map(|file| file.size).sum();
+        Ok(serde_json::to_vec_pretty(manifest)?)
+    }
+
+    /// Report the progress, at most once per interval unless forced
+    fn report(&mut self, force: bool) {
+        if !force && self.last_report.elapsed().as_millis() < env::ARCHIVE_PROGRESS_INTERVAL_MS as u128 {
+            return;
+        }
+        self.last_report = Instant::now();
+
+        let ratio = if self.total_bytes > 0 {
+            self.processed_bytes as f32 / self.total_bytes as f32
+        } else {
+            self.processed_files as f32 / self.total_files.max(1) as f32
+        };
+        self.progress.update(
+            ratio,
+            Some(format!(
+                "[{}/{}] {}",
+                self.processed_files, self.total_files, self.current_file
+            )),
+        );
+    }
+}
+
+/// Reader of a file added to an archive, counting the bytes read and stopping on cancellation
+struct TrackedReader<'t, 'a> {
+    inner: io::BufReader<File>,
+    tracker: &'t mut ArchiveTracker<'a>,
+}
+
+impl Read for TrackedReader<'_, '_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // Not `Interrupted`: the writers would retry the read
+        if self.tracker.cancellation.is_cancelled() {
+            return Err(io::Error::other("Archive writing stopped after cancellation"));
+        }
+
+        let read = self.inner.read(buf)?;
+        self.tracker.hasher.update(&buf[..read]);
+        self.tracker.file_size += read as u64;
+        self.tracker.advance(read as u64);
+        Ok(read)
+    }
+}
+
+/// Reader of a file compressed by a worker thread, hashing it and counting the bytes read in the shared progress
+struct SharedReader<'r, 't, 'a> {
+    inner: io::BufReader<File>,
+    tracker: &'r Mutex<&'t mut ArchiveTracker<'a>>,
+    cancellation: CancellationToken,
+    hasher: Sha256,
+    size: u64,
+}
+
+impl Read for SharedReader<'_, '_, '_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.cancellation.is_cancelled() {
+            return Err(io::Error::other("Archive writing stopped after cancellation"));
+        }
+
+        let read = self.inner.read(buf)?;
+        self.hasher.update(&buf[..read]);
+        self.size += read as u64;
+        self.tracker.lock().unwrap().advance(read as u64);
+        Ok(read)
+    }
+}
+
+/// Reader of exactly the given number of bytes of a file, failing if the file ends before
+struct ExactReader<'n, R> {
+    inner: R,
+    name: &'n str,
+    remaining: u64,
+}
+
+impl<R: Read> Read for ExactReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.remaining == 0 || buf.is_empty() {
+            return Ok(0);
+        }
+
+        let max = self.remaining.min(buf.len() as u64) as usize;
+        let read = self.inner.read(&mut buf[..max])?;
+        if read == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("{} has been truncated while being archived ({} bytes missing)", self.name, self.remaining),
+            ));
+        }
+        self.remaining -= read as u64;
+        Ok(read)
+    }
+}
+
+/// Seconds since UNIX epoch of a date (0 for dates before it)
+fn unix_time(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+/// Milliseconds since UNIX epoch of a date (0 for dates before it)
+pub fn unix_millis(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Convert a date to the MS-DOS format of the zip entries (UTC, the format has no time zone)
+fn zip_date_time(time: SystemTime) -> zip::DateTime {
+    let date: DateTime<Utc> = time.into();
+    zip::DateTime::from_date_and_time(
+        date.year().clamp(1980, 2107) as u16,
+        date.month() as u8,
+        date.day() as u8,
+        date.hour() as u8,
+        date.minute() as u8,
+        date.second() as u8,
+    )
+    .unwrap_or_default()
+}
+
+fn zip_error(error: zip::result::ZipError) -> crate::misc::errors::Verror {
+    MessageError(format!("Failed to write the zip archive: {}", error))
+}
+
+fn sevenz_error(error: sevenz_rust::Error) -> crate::misc::errors::Verror {
+    MessageError(format!("Failed to write the 7z archive: {}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_exact_size(data: &[u8], size: u64) -> io::Result<Vec<u8>> {
+        let mut reader = ExactReader {
+            inner: data,
+            name: "Content/Map.umap",
+            remaining: size,
+        };
+        let mut read = Vec::new();
+        reader.read_to_end(&mut read)?;
+        Ok(read)
+    }
+
+    #[test]
+    fn reads_the_listed_size() {
+        assert_eq!(read_exact_size(b"0123456789", 10).unwrap(), b"0123456789");
+        // A file that grew is cut to the size in the header
+        assert_eq!(read_exact_size(b"0123456789", 4).unwrap(), b"0123");
+    }
+
+    #[test]
+    fn fails_on_a_truncated_file() {
+        let error = read_exact_size(b"0123", 10).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+        assert!(error.to_string().contains("Content/Map.umap"));
+    }
+}
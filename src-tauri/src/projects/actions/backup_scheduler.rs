@@ -0,0 +1,317 @@
+use crate::env;
+use crate::misc::errors::Verror::{MessageError, TaskCancelledError};
+use crate::misc::errors::{ErrorLevel, Result};
+use crate::misc::prelude::log;
+use crate::misc::tasks;
+use crate::projects::actions::archive_volumes;
+use crate::projects::actions::project_compressor::{self, CompressionRequest, CompressionResult};
+use crate::projects::models::archive::ArchiveManifest;
+use crate::projects::models::backup::{BackupArchive, BackupState};
+use crate::projects::models::project::Project;
+use crate::settings::actions::settings_manager;
+use crate::settings::models::settings::BackupSchedule;
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::{command, AppHandle, Manager, State};
+
+/// # Backup Scheduler
+/// This module runs the backup schedules of the projects (see `BackupSchedule`)
+/// while the application is running. The schedules are checked periodically,
+/// a backup that has been missed while the application was closed is run once
+/// on the next check. Backups go through the compression pipeline, and the
+/// archives the schedule does not keep anymore (see `RetentionPolicy`) are
+/// deleted. Only the archives created by a schedule are ever deleted.
+
+/// Backups in progress, shared between the scheduler and the commands
+#[derive(Default)]
+pub struct BackupScheduler {
+    running: Mutex<HashSet<String>>, // Identifiers of the projects with a backup in progress
+    states: Mutex<()>,               // Serializes the updates of the backup states in the store
+}
+
+/// Current state of the backup schedule of a project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupStatus {
+    pub project_id: String,
+    pub project_path: String,         // Current path of the project (.uproject file)
+    pub enabled: bool,
+    pub running: bool,                // True while a backup of the project is in progress
+    pub next_run: Option<u64>,        // Seconds since UNIX epoch (None if disabled or never matching)
+    pub last_run: Option<u64>,        // Seconds since UNIX epoch
+    pub last_error: Option<String>,   // Error of the last backup (None if it succeeded)
+    pub archives: Vec<BackupArchive>, // Archives kept by the retention policy, oldest first
+}
+
+impl BackupScheduler {
+    /// Mark a backup of a project as running, false if one is already running
+    fn claim(&self, project_id: &str) -> bool {
+        self.running.lock().unwrap().insert(project_id.to_string())
+    }
+
+    fn release(&self, project_id: &str) {
+        self.running.lock().unwrap().remove(project_id);
+    }
+
+    fn is_running(&self, project_id: &str) -> bool {
+        self.running.lock().unwrap().contains(project_id)
+    }
+}
+
+/// Start the background loop running the backup schedules
+pub fn start_backup_scheduler(app_handle: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(std::time::Duration::from_secs(env::BACKUP_SCHEDULER_INTERVAL_SECS));
+
+        if let Err(e) = run_due_backups(&app_handle) {
+            error!("Failed to check the backup schedules: {}", e);
+        }
+    });
+}
+
+/// Get the state of the backup schedules of every project
+#[command]
+pub fn get_backup_status(app_handle: AppHandle, scheduler: State<'_, BackupScheduler>) -> Result<Vec<BackupStatus>> {
+    let settings = settings_manager::load_settings(&app_handle)?;
+    let states = BackupState::get_states(&app_handle)?;
+    let projects = Project::get_projects(&app_handle).map_err(|e| MessageError(e.to_string()))?;
+    let now = tasks::now();
+
+    let mut statuses: Vec<BackupStatus> = settings
+        .backups
+        .project_schedules
+        .iter()
+        .map(|(project_id, schedule)| {
+            let state = states
+                .iter()
+                .find(|state| state.project_id == *project_id)
+                .cloned()
+                .unwrap_or_else(|| BackupState::new(project_id, now));
+            let next_run = match (schedule.enabled, schedule.frequency.cron_schedule()) {
+                (true, Ok(cron)) => state.next_run(&cron),
+                _ => None,
+            };
+
+            BackupStatus {
+                project_id: project_id.clone(),
+                project_path: projects
+                    .iter()
+                    .find(|project| project.id == *project_id)
+                    .map(|project| project.path.display().to_string())
+                    .unwrap_or_default(),
+                enabled: schedule.enabled,
+                running: scheduler.is_running(project_id),
+                next_run: next_run.map(|next_run| next_run.max(state.retry_at.unwrap_or(0))),
+                last_run: state.last_run,
+                last_error: state.last_error,
+                archives: state.archives,
+            }
+        })
+        .collect();
+    statuses.sort_by(|a, b| a.project_path.cmp(&b.project_path));
+
+    Ok(statuses)
+}
+
+/// Run the backup schedule of a project now, whatever its next run is
+#[command]
+pub async fn run_backup_now(app_handle: AppHandle, project_path: String) -> Result<CompressionResult> {
+    let project_id = Project::resolve_id(&app_handle, &project_path)?;
+    let settings = settings_manager::load_settings(&app_handle)?;
+    let schedule = settings
+        .backups
+        .project_schedules
+        .get(&project_id)
+        .cloned()
+        .ok_or_else(|| MessageError(format!("No backup schedule for {}", project_path)))?;
+
+    if !app_handle.state::<BackupScheduler>().claim(&project_id) {
+        return Err(MessageError("A backup of this project is already running".to_string()));
+    }
+    let result = run_backup(&app_handle, &project_id, &schedule).await;
+    app_handle.state::<BackupScheduler>().release(&project_id);
+
+    result
+}
+
+/// Start the backups that are due. Schedules found for the first time start counting from now.
+fn run_due_backups(app_handle: &AppHandle) -> Result<()> {
+    let settings = settings_manager::load_settings(app_handle)?;
+    let scheduler = app_handle.state::<BackupScheduler>();
+    let now = tasks::now();
+
+    let due_backups: Vec<(String, BackupSchedule)> = {
+        let _states_guard = scheduler.states.lock().unwrap();
+        let mut states = BackupState::get_states(app_handle)?;
+        let states_count = states.len();
+
+        let mut due_backups = Vec::new();
+        for (project_id, schedule) in settings.backups.project_schedules.iter().filter(|(_, s)| s.enabled) {
+            let cron = match schedule.frequency.cron_schedule() {
+                Ok(cron) => cron,
+                Err(e) => {
+                    warn!("Skipped the backup schedule of {}: {}", project_id, e);
+                    continue;
+                }
+            };
+
+            let state = match states.iter().position(|state| state.project_id == *project_id) {
+                Some(index) => &states[index],
+                None => {
+                    states.push(BackupState::new(project_id, now));
+                    &states[states.len() - 1]
+                }
+            };
+            if state.is_due(&cron, now) {
+                due_backups.push((project_id.clone(), schedule.clone()));
+            }
+        }
+
+        if states.len() != states_count {
+            BackupState::save_states(app_handle, &states)?;
+        }
+        due_backups
+    };
+
+    for (project_id, schedule) in due_backups {
+        if !scheduler.claim(&project_id) {
+            continue;
+        }
+
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            // The outcome is recorded in the backup state
+            let _ = run_backup(&app_handle, &project_id, &schedule).await;
+            app_handle.state::<BackupScheduler>().release(&project_id);
+        });
+    }
+
+    Ok(())
+}
+
+/// Create a backup of a project, record it and prune the archives of the schedule
+async fn run_backup(app_handle: &AppHandle, project_id: &str, schedule: &BackupSchedule) -> Result<CompressionResult> {
+    let project_path = Project::resolve_path(app_handle, project_id).display().to_string();
+    info!("Running the scheduled backup of {}", project_path);
+    log(
+        app_handle,
+        ErrorLevel::Info,
+        &format!("Starting the scheduled backup of {}", project_path),
+    );
+
+    let result = match fs::create_dir_all(&schedule.destination_path) {
+        Ok(_) => {
+            let request = CompressionRequest {
+                project_path: project_path.clone(),
+                destination_path: schedule.destination_path.clone(),
+                compression_algorithm: schedule.compression_algorithm.clone(),
+                clean_before_compress: false,
+                cleaning_selection: None,
+                force: false,
+                rules: schedule.rules.clone(),
+                write_manifest_file: false,
+                max_volume_size: schedule.max_volume_size,
+                backup_parent: None,
+                options: schedule.options.clone(),
+            };
+            project_compressor::compress_project(app_handle.clone(), request).await
+        }
+        Err(e) => Err(MessageError(format!(
+            "Failed to create the backup directory {}: {}",
+            schedule.destination_path, e
+        ))),
+    };
+
+    let now = tasks::now();
+    let scheduler = app_handle.state::<BackupScheduler>();
+    let _states_guard = scheduler.states.lock().unwrap();
+    let mut states = BackupState::get_states(app_handle)?;
+    let index = match states.iter().position(|state| state.project_id == project_id) {
+        Some(index) => index,
+        None => {
+            states.push(BackupState::new(project_id, now));
+            states.len() - 1
+        }
+    };
+    let state = &mut states[index];
+
+    match &result {
+        Ok(compression) => {
+            state.last_run = Some(now);
+            state.retry_at = None;
+            state.last_error = None;
+            state.archives.push(BackupArchive {
+                path: compression.output_path.clone(),
+                created_at: now,
+            });
+
+            for archive in state.pruned_archives(&schedule.retention, now) {
+                match delete_archive(Path::new(&archive.path)) {
+                    Ok(_) => {
+                        info!("Pruned the backup {}", archive.path);
+                        state.archives.retain(|kept| kept.path != archive.path);
+                    }
+                    // Tried again after the next backup
+                    Err(e) => error!("Failed to prune the backup {}: {}", archive.path, e),
+                }
+            }
+        }
+        // A backup cancelled by the user is skipped until the next scheduled run
+        Err(TaskCancelledError(_)) => {
+            state.last_run = Some(now);
+            state.retry_at = None;
+            state.last_error = Some("Cancelled".to_string());
+        }
+        Err(e) => {
+            state.retry_at = Some(now + env::BACKUP_RETRY_DELAY_SECS);
+            state.last_error = Some(e.to_string());
+            log(
+                app_handle,
+                ErrorLevel::Error,
+                &format!("The scheduled backup of {} failed, it will be tried again later: {}", project_path, e),
+            );
+        }
+    }
+
+    BackupState::save_states(app_handle, &states)?;
+    result
+}
+
+/// Move the backup state of a project to another project, merged with the state of its own schedule
+pub fn move_backup_state(app_handle: &AppHandle, from_id: &str, into_id: &str) -> Result<()> {
+    let scheduler = app_handle.state::<BackupScheduler>();
+    let _states_guard = scheduler.states.lock().unwrap();
+    let mut states = BackupState::get_states(app_handle)?;
+
+    let Some(index) = states.iter().position(|state| state.project_id == from_id) else {
+        return Ok(());
+    };
+    let moved_state = states.remove(index);
+    match states.iter_mut().find(|state| state.project_id == into_id) {
+        Some(state) => state.merge(moved_state),
+        None => states.push(BackupState {
+            project_id: into_id.to_string(),
+            ..moved_state
+        }),
+    }
+
+    BackupState::save_states(app_handle, &states)
+}
+
+/// Delete an archive (every volume of a split archive) and its manifest file
+fn delete_archive(archive_path: &Path) -> Result<()> {
+    // An archive already deleted by the user is not an error
+    for file in archive_volumes::archive_files(archive_path) {
+        fs::remove_file(file)?;
+    }
+
+    let sidecar_path = ArchiveManifest::sidecar_path(archive_path);
+    if sidecar_path.is_file() {
+        fs::remove_file(sidecar_path)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,318 @@
+use crate::env;
+use crate::misc::errors::Result;
+use crate::misc::errors::Verror::MessageError;
+use crate::misc::progress::TaskProgress;
+use crate::misc::tasks::CancellationToken;
+use crate::projects::actions::archive_writer::archive_name;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use log::warn;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Condvar, Mutex};
+use std::time::Instant;
+
+/// # Discovery Walker
+/// This module searches a folder for the project files (.uproject) with several
+/// threads. The directories that cannot hold a project (build outputs, caches,
+/// version control data, see `DISCOVERY_PRUNED_DIRS`) and the directories excluded
+/// by the user are never walked, and the walk does not go below a directory holding
+/// a project. Symbolic links are followed, each directory is walked once, so a link
+/// to one of its parents does not loop.
+/// The progress is reported as the directories are visited.
+
+/// Directories skipped by a walk, checked before walking them
+pub struct DiscoveryFilter {
+    pruned_names: HashSet<String>, // Lowercase names of the directories never walked
+    excluded: GlobSet,             // Paths relative to the walked folder, '/' separators
+}
+
+/// Outcome of the walk of a folder
+#[derive(Debug, Clone, Default)]
+pub struct DiscoveryWalk {
+    pub project_files: Vec<PathBuf>, // Project files found, sorted
+    pub visited_dirs: usize,         // Directories listed
+    pub skipped_dirs: usize,         // Directories pruned, excluded or already visited through a link
+    pub unreadable_dirs: usize,      // Directories that could not be listed (e.g. access denied)
+}
+
+impl DiscoveryFilter {
+    pub fn new(
+        ignore_templates: bool,
+        ignore_engine: bool,
+        ignore_samples: bool,
+        excluded_patterns: &[String],
+    ) -> Result<Self> {
+        let mut pruned_names: HashSet<String> = env::DISCOVERY_PRUNED_DIRS
+            .iter()
+            .map(|name| name.to_lowercase())
+            .collect();
+        for (ignored, name) in [
+            (ignore_templates, "templates"),
+            (ignore_engine, "engine"),
+            (ignore_samples, "samples"),
+        ] {
+            if ignored {
+                pruned_names.insert(name.to_string());
+            }
+        }
+
+        let mut builder = GlobSetBuilder::new();
+        for pattern in excluded_patterns.iter().map(|pattern| pattern.trim()).filter(|pattern| !pattern.is_empty()) {
+            let glob = GlobBuilder::new(pattern.trim_start_matches('/').trim_end_matches('/'))
+                .literal_separator(true)
+                .case_insensitive(true)
+                .build()
+                .map_err(|e| MessageError(format!("Invalid discovery exclusion \"{}\": {}", pattern, e)))?;
+            builder.add(glob);
+        }
+        let excluded = builder
+            .build()
+            .map_err(|e| MessageError(format!("Invalid discovery exclusions: {}", e)))?;
+
+        Ok(Self { pruned_names, excluded })
+    }
+
+    /// Check if a directory is skipped, from its path relative to the walked folder
+    pub fn is_skipped(&self, relative_path: &Path) -> bool {
+        let name = relative_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        // "Backups/**" also excludes the "Backups" directory itself
+        let relative_name = archive_name(relative_path);
+        self.pruned_names.contains(&name)
+            || self.excluded.is_match(&relative_name)
+            || self.excluded.is_match(format!("{}/", relative_name))
+    }
+}
+
+/// A directory waiting to be visited
+struct PendingDir {
+    path: PathBuf,      // Path under the walked folder, as reported
+    real_path: PathBuf, // Path without symbolic links, identifies the directory
+}
+
+/// State of a walk shared by the worker threads
+struct WalkState {
+    pending: Vec<PendingDir>,
+    active: usize,        // Directories being listed by a worker
+    stopped: bool,        // Set on cancellation
+    walk: DiscoveryWalk,
+    last_ratio: f32,      // The reported progress never goes back
+    last_report: Instant,
+}
+
+struct Walker<'a> {
+    root: &'a Path,
+    filter: &'a DiscoveryFilter,
+    progress: &'a TaskProgress,
+    cancellation: CancellationToken,
+    state: Mutex<WalkState>,
+    wakeup: Condvar,                     // Signaled when directories are queued or the walk ends
+    known_dirs: Mutex<HashSet<PathBuf>>, // Real paths of the directories queued so far
+}
+
+/// Search a folder for project files. The progress goes from 0.0 to 1.0 as the directories are visited.
+pub fn find_project_files(root: &Path, filter: &DiscoveryFilter, progress: &TaskProgress) -> Result<DiscoveryWalk> {
+    let real_root = fs::canonicalize(root)
+        .map_err(|e| MessageError(format!("Cannot read the folder {}: {}", root.display(), e)))?;
+    if !real_root.is_dir() {
+        return Err(MessageError(format!("{} is not a folder", root.display())));
+    }
+
+    let walker = Walker {
+        root,
+        filter,
+        progress,
+        cancellation: progress.cancellation_token(),
+        state: Mutex::new(WalkState {
+            pending: vec![PendingDir {
+                path: root.to_path_buf(),
+                real_path: real_root.clone(),
+            }],
+            active: 0,
+            stopped: false,
+            walk: DiscoveryWalk::default(),
+            last_ratio: 0.0,
+            last_report: Instant::now(),
+        }),
+        wakeup: Condvar::new(),
+        known_dirs: Mutex::new(HashSet::from([real_root])),
+    };
+
+    let threads = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .clamp(1, env::DISCOVERY_MAX_THREADS);
+    std::thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| walker.run());
+        }
+    });
+
+    progress.check_cancelled()?;
+
+    let mut walk = walker.state.into_inner().unwrap().walk;
+    walk.project_files.sort();
+    progress.update(
+        1.0,
+        Some(format!(
+            "Visited {} directories, found {} project(s)",
+            walk.visited_dirs,
+            walk.project_files.len()
+        )),
+    );
+
+    Ok(walk)
+}
+
+impl Walker<'_> {
+    /// Visit the queued directories until there are none left and no worker can queue more
+    fn run(&self) {
+        loop {
+            let dir = {
+                let mut state = self.state.lock().unwrap();
+                loop {
+                    if state.stopped {
+                        return;
+                    }
+                    if let Some(dir) = state.pending.pop() {
+                        state.active += 1;
+                        break dir;
+                    }
+                    if state.active == 0 {
+                        return;
+                    }
+                    state = self.wakeup.wait(state).unwrap();
+                }
+            };
+
+            let visit = self.visit(&dir);
+
+            let mut state = self.state.lock().unwrap();
+            state.active -= 1;
+            state.walk.visited_dirs += 1;
+            state.walk.skipped_dirs += visit.skipped_dirs;
+            state.walk.unreadable_dirs += visit.unreadable as usize;
+            state.walk.project_files.extend(visit.project_files);
+            state.pending.extend(visit.subdirs);
+            if self.cancellation.is_cancelled() {
+                state.stopped = true;
+            }
+            self.report(&mut state);
+
+            // Wakes the idle workers for the new directories, or to end the walk
+            self.wakeup.notify_all();
+        }
+    }
+
+    /// List a directory: its project files, or the subdirectories to visit if it holds none
+    fn visit(&self, dir: &PendingDir) -> DirVisit {
+        let mut visit = DirVisit::default();
+        let entries = match fs::read_dir(&dir.path) {
+            Ok(entries) => entries,
+            Err(e) => {
+                warn!("Cannot list {} while searching for projects: {}", dir.path.display(), e);
+                visit.unreadable = true;
+                return visit;
+            }
+        };
+
+        let mut subdirs = Vec::new();
+        for entry in entries.flatten() {
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let path = entry.path();
+
+            // The target of a link decides what it is
+            let (is_dir, is_file) = if file_type.is_symlink() {
+                match fs::metadata(&path) {
+                    Ok(metadata) => (metadata.is_dir(), metadata.is_file()),
+                    Err(_) => continue, // Broken link
+                }
+            } else {
+                (file_type.is_dir(), file_type.is_file())
+            };
+
+            if is_file && is_project_file(&path) {
+                visit.project_files.push(path);
+            } else if is_dir {
+                subdirs.push((path, file_type.is_symlink()));
+            }
+        }
+
+        // A project holds no other project, its content is not walked
+        if !visit.project_files.is_empty() {
+            return visit;
+        }
+
+        for (path, is_link) in subdirs {
+            let skipped = path
+                .strip_prefix(self.root)
+                .map(|relative_path| self.filter.is_skipped(relative_path))
+                .unwrap_or(false);
+            if skipped {
+                visit.skipped_dirs += 1;
+                continue;
+            }
+
+            let real_path = if is_link {
+                match fs::canonicalize(&path) {
+                    Ok(real_path) => real_path,
+                    Err(_) => continue,
+                }
+            } else {
+                dir.real_path.join(path.file_name().unwrap_or_default())
+            };
+
+            // A directory reached again through a link (or a link to a parent) is not walked twice
+            if !self.known_dirs.lock().unwrap().insert(real_path.clone()) {
+                visit.skipped_dirs += 1;
+                continue;
+            }
+            visit.subdirs.push(PendingDir { path, real_path });
+        }
+
+        visit
+    }
+
+    /// Report the progress, at most once per interval. The share of the known directories
+    /// already visited is used, the total is not known before the end of the walk.
+    fn report(&self, state: &mut WalkState) {
+        if state.last_report.elapsed().as_millis() < env::DISCOVERY_PROGRESS_INTERVAL_MS as u128 {
+            return;
+        }
+        state.last_report = Instant::now();
+
+        let known = state.walk.visited_dirs + state.pending.len() + state.active;
+        let ratio = state.walk.visited_dirs as f32 / known.max(1) as f32;
+        state.last_ratio = state.last_ratio.max(ratio);
+        self.progress.update(
+            state.last_ratio,
+            Some(format!(
+                "Visited {} directories, found {} project(s)",
+                state.walk.visited_dirs,
+                state.walk.project_files.len()
+            )),
+        );
+    }
+}
+
+/// Outcome of the visit of a directory
+#[derive(Default)]
+struct DirVisit {
+    subdirs: Vec<PendingDir>,
+    project_files: Vec<PathBuf>,
+    skipped_dirs: usize,
+    unreadable: bool,
+}
+
+/// Check if a path is a project file (.uproject, any letter case)
+pub fn is_project_file(path: &Path) -> bool {
+    path.extension()
+        .map(|extension| extension.eq_ignore_ascii_case("uproject"))
+        .unwrap_or(false)
+}
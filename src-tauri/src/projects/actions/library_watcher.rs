@@ -0,0 +1,404 @@
+use crate::env;
+use crate::misc::errors::Verror::MessageError;
+use crate::misc::errors::{ErrorLevel, Result};
+use crate::misc::prelude::log;
+use crate::misc::progress::TaskProgress;
+use crate::misc::tasks::TaskKind;
+use crate::projects::actions::discovery_walker::{self, DiscoveryFilter};
+use crate::projects::actions::project_health;
+use crate::projects::models::project::Project;
+use crate::settings::actions::settings_manager;
+use log::{error, info, warn};
+use notify::event::ModifyKind;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use uuid::Uuid;
+
+/// # Library Watcher
+/// This module watches the library folders (see `DiscoverySettings::library_roots`)
+/// while the application is running. A project file added to a library is registered,
+/// a project deleted or moved out of a library is flagged as missing (it stays in the
+/// list with its settings and history), and an edited project file is scanned again.
+/// A library is searched when it starts being watched, to catch up with the changes
+/// made while the application was closed. The directories skipped by the discovery
+/// (see `DiscoveryFilter`) are ignored.
+
+/// Watcher of the library folders, shared between the event loop and the settings
+#[derive(Default)]
+pub struct LibraryWatcher {
+    watcher: Mutex<Option<RecommendedWatcher>>, // None if the watcher could not be started
+    roots: Mutex<Vec<PathBuf>>,                 // Library folders being watched
+    pub(crate) updates: Mutex<()>,              // Serializes the updates of the projects made by the watcher and the health checks
+}
+
+/// Changes of the project files under some paths of the libraries
+#[derive(Debug, Default)]
+struct LibraryChanges {
+    found: BTreeSet<PathBuf>,  // Project files created or moved in
+    edited: BTreeSet<PathBuf>, // Project files written
+    scope: BTreeSet<PathBuf>,  // Paths whose tracked projects are checked (deleted or moved out)
+}
+
+/// Start watching the library folders of the settings
+pub fn start_library_watcher(app_handle: AppHandle) {
+    let (sender, receiver) = mpsc::channel();
+    let watcher = notify::recommended_watcher(move |event| {
+        // The receiver only stops with the application
+        let _ = sender.send(event);
+    });
+
+    match watcher {
+        Ok(watcher) => *app_handle.state::<LibraryWatcher>().watcher.lock().unwrap() = Some(watcher),
+        Err(e) => {
+            error!("Failed to start the library watcher: {}", e);
+            return;
+        }
+    }
+
+    let events_app_handle = app_handle.clone();
+    std::thread::spawn(move || process_events(&events_app_handle, receiver));
+
+    if let Err(e) = watch_library_roots(&app_handle) {
+        error!("Failed to watch the library folders: {}", e);
+    }
+}
+
+/// Watch the library folders of the settings. The folders that were not watched yet are searched for projects.
+pub fn watch_library_roots(app_handle: &AppHandle) -> Result<()> {
+    let settings = settings_manager::load_settings(app_handle)?;
+    let library = app_handle.state::<LibraryWatcher>();
+    let mut watcher = library.watcher.lock().unwrap();
+    let Some(watcher) = watcher.as_mut() else {
+        return Ok(()); // The error has been logged on startup
+    };
+
+    let wanted: Vec<PathBuf> = settings.discovery.library_roots.iter().map(PathBuf::from).collect();
+    let mut roots = library.roots.lock().unwrap();
+
+    for root in roots.iter().filter(|root| !wanted.contains(root)) {
+        // A deleted folder is not watched anymore
+        if let Err(e) = watcher.unwatch(root) {
+            warn!("Failed to stop watching the library {}: {}", root.display(), e);
+        }
+        info!("Stopped watching the library {}", root.display());
+    }
+    roots.retain(|root| wanted.contains(root));
+
+    for root in wanted {
+        if roots.contains(&root) {
+            continue;
+        }
+
+        match watcher.watch(&root, RecursiveMode::Recursive) {
+            Ok(_) => {
+                info!("Watching the library {}", root.display());
+                roots.push(root.clone());
+
+                let app_handle = app_handle.clone();
+                tauri::async_runtime::spawn(async move { scan_library(&app_handle, &root).await });
+            }
+            Err(e) => log(
+                app_handle,
+                ErrorLevel::Warning,
+                &format!("Cannot watch the library {}: {}", root.display(), e),
+            ),
+        }
+    }
+
+    Ok(())
+}
+
+/// Search a library for the projects added, deleted or moved while it was not watched
+async fn scan_library(app_handle: &AppHandle, root: &Path) {
+    let dirs = vec![(root.to_path_buf(), root.to_path_buf())];
+    scan_directories(app_handle, dirs, format!("Scanning the library {}", root.display())).await
+}
+
+/// Search directories of the libraries for projects, in a queued discovery task.
+/// Each directory comes with its library, the exclusions are relative to the library.
+async fn scan_directories(app_handle: &AppHandle, dirs: Vec<(PathBuf, PathBuf)>, task_name: String) {
+    // One scan per library root may start at once
+    let task_id = format!("scan_library_{}", Uuid::new_v4().simple());
+    let progress = match TaskProgress::queued(
+        app_handle.clone(),
+        task_id,
+        task_name.clone(),
+        TaskKind::Discovery,
+        None,
+    )
+    .await
+    {
+        Ok(progress) => progress,
+        Err(e) => {
+            warn!("{} not started: {}", task_name, e);
+            return;
+        }
+    };
+
+    let changes = library_filter(app_handle).and_then(|filter| {
+        let mut changes = LibraryChanges::default();
+        let weight = 0.9 / dirs.len().max(1) as f32;
+        for (index, (dir, root)) in dirs.iter().enumerate() {
+            // A directory deleted since, or a library folder on a drive that is not there
+            if !dir.is_dir() {
+                warn!("{} not searched for projects, it does not exist anymore", dir.display());
+                continue;
+            }
+
+            let dir_progress = progress.sub_task(index as f32 * weight, weight, &dir.display().to_string());
+            let walk = discovery_walker::find_project_files(dir, &filter, &dir_progress)?;
+            changes.found.extend(
+                walk.project_files
+                    .into_iter()
+                    .filter(|project_file| !is_filtered(&filter, root, project_file)),
+            );
+            changes.scope.insert(dir.clone());
+        }
+        Ok(changes)
+    });
+
+    let result = changes.and_then(|changes| {
+        apply_changes(app_handle, &changes)?;
+        Ok(changes.found.len())
+    });
+    match result {
+        Ok(found) => progress.complete(Some(format!("Found {} project(s)", found))),
+        Err(_) if progress.is_cancelled() => progress.cancelled(None),
+        Err(e) => {
+            log(
+                app_handle,
+                ErrorLevel::Error,
+                &format!("{} failed: {}", task_name, e),
+            );
+            progress.fail(Some(e.to_string()));
+        }
+    }
+}
+
+/// Apply the file system events by batches, gathered for `LIBRARY_WATCHER_DEBOUNCE_MS`
+/// (saving a file often produces several events)
+fn process_events(app_handle: &AppHandle, receiver: Receiver<notify::Result<Event>>) {
+    while let Ok(first_event) = receiver.recv() {
+        let mut events = vec![first_event];
+        let deadline = Instant::now() + Duration::from_millis(env::LIBRARY_WATCHER_DEBOUNCE_MS);
+        while let Ok(event) = receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            events.push(event);
+        }
+
+        if let Err(e) = apply_events(app_handle, events) {
+            error!("Failed to apply the changes of the library folders: {}", e);
+        }
+    }
+}
+
+fn apply_events(app_handle: &AppHandle, events: Vec<notify::Result<Event>>) -> Result<()> {
+    let roots = app_handle.state::<LibraryWatcher>().roots.lock().unwrap().clone();
+    let filter = library_filter(app_handle)?;
+    let project_dirs: Vec<PathBuf> = Project::get_projects(app_handle)
+        .map_err(|e| MessageError(e.to_string()))?
+        .iter()
+        .filter_map(|project| project.path.parent().map(Path::to_path_buf))
+        .collect();
+
+    // Like the discovery, the content of a project is not searched for other projects
+    let inside_project = |path: &Path| {
+        project_dirs
+            .iter()
+            .any(|project_dir| path.starts_with(project_dir) && path.parent() != Some(project_dir))
+    };
+
+    let mut changes = LibraryChanges::default();
+    let mut new_dirs = BTreeSet::new();
+    for event in events {
+        let event = match event {
+            // Files opened and closed (the walks of the libraries included) change nothing
+            Ok(event) if matches!(event.kind, EventKind::Access(_)) => continue,
+            Ok(event) => event,
+            Err(e) => {
+                warn!("Library watcher error: {}", e);
+                continue;
+            }
+        };
+
+        for path in event.paths {
+            let Some(root) = roots.iter().find(|root| path.starts_with(root)) else {
+                continue;
+            };
+            if is_filtered(&filter, root, &path) {
+                continue;
+            }
+            changes.scope.insert(path.clone());
+
+            // A rename is reported with the old path, the new path, or both
+            let added = matches!(event.kind, EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(_)));
+            let written = matches!(event.kind, EventKind::Modify(ModifyKind::Data(_) | ModifyKind::Any));
+            if added && path.is_dir() && !inside_project(&path) {
+                new_dirs.insert(path);
+            } else if discovery_walker::is_project_file(&path) && !inside_project(&path) {
+                if added && path.is_file() {
+                    changes.found.insert(path);
+                } else if written {
+                    changes.edited.insert(path);
+                }
+            }
+        }
+    }
+
+    // The content of a directory created or moved in is not reported, it is searched by a
+    // discovery task, queued like the other discoveries (a directory inside another one is
+    // searched with it)
+    let dirs: Vec<(PathBuf, PathBuf)> = new_dirs
+        .iter()
+        .filter(|dir| !new_dirs.iter().any(|other| other != *dir && dir.starts_with(other)))
+        .filter_map(|dir| {
+            let root = roots.iter().find(|root| dir.starts_with(root))?;
+            Some((dir.clone(), root.clone()))
+        })
+        .collect();
+    if !dirs.is_empty() {
+        let task_name = match &dirs[..] {
+            [(dir, _)] => format!("Searching {} for projects", dir.display()),
+            _ => format!("Searching {} new folders for projects", dirs.len()),
+        };
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move { scan_directories(&app_handle, dirs, task_name).await });
+    }
+
+    if changes.scope.is_empty() {
+        return Ok(());
+    }
+    apply_changes(app_handle, &changes)
+}
+
+/// Register the projects found, scan the edited ones again and flag the missing ones.
+/// The projects are saved (and `projects_updated` emitted) only if something changed.
+fn apply_changes(app_handle: &AppHandle, changes: &LibraryChanges) -> Result<()> {
+    let library = app_handle.state::<LibraryWatcher>();
+    let _updates_guard = library.updates.lock().unwrap();
+    let mut projects = Project::get_projects(app_handle).map_err(|e| MessageError(e.to_string()))?;
+
+    let mut registered = Vec::new();
+    let mut relinked = Vec::new();
+    let mut rescanned = Vec::new();
+    let mut flagged = Vec::new();
+    for project in projects
+        .iter_mut()
+        .filter(|project| changes.scope.iter().any(|path| project.path.starts_with(path)))
+    {
+        if !project.path.is_file() {
+            if !project.missing {
+                project.missing = true;
+                flagged.push(project.path.clone());
+            }
+            continue;
+        }
+
+        // A project written again, replaced, or back where it was is scanned again
+        let rescan = project.missing || changes.edited.contains(&project.path) || changes.found.contains(&project.path);
+        if !rescan {
+            continue;
+        }
+        match Project::try_from_path(&project.path) {
+            Ok(scanned) => {
+                *project = Project { id: project.id.clone(), ..scanned };
+                rescanned.push(project.path.clone());
+            }
+            // Most likely a file being written, the next event scans it again
+            Err(e) => warn!("Failed to scan the project {}: {}", project.path.display(), e),
+        }
+    }
+
+    for path in &changes.found {
+        if projects.iter().any(|project| project.path == *path) {
+            continue;
+        }
+        let scanned = match Project::try_from_path(path) {
+            Ok(scanned) => scanned,
+            Err(e) => {
+                warn!("Failed to register the project {}: {}", path.display(), e);
+                continue;
+            }
+        };
+
+        // A missing project moved inside the libraries is relinked, it keeps its identifier and its data
+        let moved_projects: Vec<usize> = projects
+            .iter()
+            .enumerate()
+            .filter(|(_, project)| project.missing)
+            .filter(|(_, project)| {
+                project_health::find_candidates(project, std::slice::from_ref(path))
+                    .iter()
+                    .any(|candidate| candidate.matching_files == candidate.hashed_files)
+            })
+            .map(|(index, _)| index)
+            .collect();
+        if let [index] = moved_projects[..] {
+            let project = &mut projects[index];
+            relinked.push(project.path.clone());
+            *project = Project { id: project.id.clone(), ..scanned };
+            continue;
+        }
+
+        let mut project = scanned;
+        project.ensure_unique_id(&projects);
+        registered.push(project.path.clone());
+        projects.push(project);
+    }
+
+    if registered.is_empty() && relinked.is_empty() && rescanned.is_empty() && flagged.is_empty() {
+        return Ok(());
+    }
+
+    Project::save_projects(app_handle, &projects).map_err(|e| MessageError(e.to_string()))?;
+    for (paths, action) in [
+        (&registered, "Registered the project"),
+        (&relinked, "Relinked the moved project"),
+        (&rescanned, "Scanned the project"),
+        (&flagged, "The project is missing"),
+    ] {
+        for path in paths {
+            info!("{} {}", action, path.display());
+        }
+    }
+    log(
+        app_handle,
+        ErrorLevel::Info,
+        &format!(
+            "Library folders: {} project(s) added, {} moved, {} updated, {} missing",
+            registered.len(),
+            relinked.len(),
+            rescanned.len(),
+            flagged.len()
+        ),
+    );
+
+    Ok(())
+}
+
+/// Filter of the library folders: the exclusions of the settings and, like the default
+/// discovery, the engine, templates and samples folders
+pub fn library_filter(app_handle: &AppHandle) -> Result<DiscoveryFilter> {
+    let settings = settings_manager::load_settings(app_handle)?;
+    DiscoveryFilter::new(true, true, true, &settings.discovery.excluded_patterns)
+}
+
+/// Check if a path of a library is in a directory skipped by the discovery
+fn is_filtered(filter: &DiscoveryFilter, root: &Path, path: &Path) -> bool {
+    let Ok(relative_path) = path.strip_prefix(root) else {
+        return true;
+    };
+    let Some(parent) = relative_path.parent() else {
+        return false;
+    };
+
+    parent
+        .ancestors()
+        .filter(|ancestor| !ancestor.as_os_str().is_empty())
+        .any(|ancestor| filter.is_skipped(ancestor))
+        || (!discovery_walker::is_project_file(path) && filter.is_skipped(relative_path))
+}
@@ -3,4 +3,18 @@ pub mod behavior;
 pub mod project_launcher;
 pub mod project_cleaner;
 pub mod project_compressor;
-pub mod plugin_manager;
\ No newline at end of file
+pub mod plugin_manager;
+pub mod network_launcher;
+pub mod process_supervisor;
+pub mod project_lock;
+pub mod session_tracker;
+pub mod project_builder;
+pub mod archive_writer;
+pub mod archive_reader;
+pub mod project_extractor;
+pub mod archive_verifier;
+pub mod archive_volumes;
+pub mod backup_scheduler;
+pub mod discovery_walker;
+pub mod library_watcher;
+pub mod project_health;
\ No newline at end of file
@@ -0,0 +1,271 @@
+use crate::engines::models::engine::{EngineInstall, EngineVersion};
+use crate::misc::errors::ErrorLevel;
+use crate::misc::prelude::log;
+use crate::projects::actions::process_supervisor::ProcessSupervisor;
+use crate::projects::actions::project_launcher::resolve_project_editor;
+use crate::projects::models::process::{ProcessKind, RunningProcess};
+use crate::projects::models::project::Project;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+use std::sync::Mutex;
+use tauri::{command, AppHandle, Manager, State};
+
+/// # Network Test Launcher
+/// This module starts a dedicated server and several game clients of a project
+/// connected to it, to test multiplayer features locally. All the processes of
+/// a test are tracked by the process supervisor as a single group that can be
+/// stopped at once.
+
+/// Default window size of a client when the screen size cannot be determined
+const DEFAULT_CLIENT_RESOLUTION: (u32, u32) = (1280, 720);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkTestRequest {
+    pub project_path: String,
+    pub client_count: u32,
+    pub map: Option<String>,                   // Map loaded by the server
+    pub port: u16,                             // Port the server listens on
+    pub client_resolution: Option<(u32, u32)>, // Window size of the clients (fit to the screen if None)
+    pub log: bool,                             // Open a log window for each instance
+    pub extra_arguments: Vec<String>,          // Additional arguments passed to every instance
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkTestSession {
+    pub session_id: String,
+    pub project_path: String,
+    pub server_pid: u32,
+    pub client_pids: Vec<u32>,
+}
+
+/// Running network test sessions, keyed by session id (also the process group id)
+#[derive(Default)]
+pub struct NetworkTestSessions {
+    sessions: Mutex<HashMap<String, NetworkTestSession>>,
+}
+
+/// Start a dedicated server and N clients connected to it
+#[command]
+pub async fn launch_network_test(
+    app_handle: AppHandle,
+    sessions: State<'_, NetworkTestSessions>,
+    supervisor: State<'_, ProcessSupervisor>,
+    request: NetworkTestRequest,
+) -> Result<NetworkTestSession, String> {
+    let project_path = Project::resolve_path(&app_handle, &request.project_path);
+
+    if !project_path.exists() {
+        let error_msg = format!("Project file does not exist: {}", project_path.display());
+        log(&app_handle, ErrorLevel::Error, &error_msg);
+        return Err(error_msg);
+    }
+
+    if request.client_count == 0 {
+        return Err("A network test needs at least one client".to_string());
+    }
+
+    let (_, engine) = resolve_project_editor(&app_handle, &project_path)?;
+    let session_id = format!("network_test_{}", chrono::Utc::now().timestamp_millis());
+    let mut instances: Vec<RunningProcess> = Vec::new();
+
+    // Step 1: Start the dedicated server
+    let mut server_arguments = vec![project_path.display().to_string()];
+    if let Some(map) = request.map.as_ref().filter(|map| !map.is_empty()) {
+        server_arguments.push(map.clone());
+    }
+    server_arguments.push("-server".to_string());
+    server_arguments.push(format!("-port={}", request.port));
+    server_arguments.extend(common_arguments(&request));
+
+    match spawn_instance(
+        &app_handle,
+        &supervisor,
+        &session_id,
+        ProcessKind::Server,
+        &engine,
+        &project_path,
+        &server_arguments,
+    ) {
+        Ok(instance) => instances.push(instance),
+        Err(e) => {
+            let error_msg = format!("Failed to start the dedicated server: {}", e);
+            log(&app_handle, ErrorLevel::Error, &error_msg);
+            return Err(error_msg);
+        }
+    }
+
+    // Step 2: Start the clients, laid out on a grid
+    let layout = client_layout(&app_handle, request.client_count, request.client_resolution);
+
+    for (index, (x, y, width, height)) in layout.into_iter().enumerate() {
+        let mut client_arguments = vec![
+            project_path.display().to_string(),
+            format!("127.0.0.1:{}", request.port),
+            "-game".to_string(),
+            "-windowed".to_string(),
+            format!("-ResX={}", width),
+            format!("-ResY={}", height),
+            format!("-WinX={}", x),
+            format!("-WinY={}", y),
+        ];
+        client_arguments.extend(common_arguments(&request));
+
+        match spawn_instance(
+            &app_handle,
+            &supervisor,
+            &session_id,
+            ProcessKind::Game,
+            &engine,
+            &project_path,
+            &client_arguments,
+        ) {
+            Ok(instance) => instances.push(instance),
+            Err(e) => {
+                // Do not leave a partial session running
+                let error_msg = format!("Failed to start client {}: {}", index + 1, e);
+                log(&app_handle, ErrorLevel::Error, &error_msg);
+                supervisor.kill_group(&session_id);
+                return Err(error_msg);
+            }
+        }
+    }
+
+    let session = NetworkTestSession {
+        session_id: session_id.clone(),
+        project_path: project_path.display().to_string(),
+        server_pid: instances[0].pid,
+        client_pids: instances[1..].iter().map(|instance| instance.pid).collect(),
+    };
+
+    log(
+        &app_handle,
+        ErrorLevel::Info,
+        &format!(
+            "Network test started with a server and {} client(s) on port {}",
+            request.client_count, request.port
+        ),
+    );
+
+    sessions
+        .sessions
+        .lock()
+        .map_err(|e| e.to_string())?
+        .insert(session_id, session.clone());
+
+    Ok(session)
+}
+
+/// Stop every process of a network test session
+#[command]
+pub fn stop_network_test(
+    app_handle: AppHandle,
+    sessions: State<'_, NetworkTestSessions>,
+    supervisor: State<'_, ProcessSupervisor>,
+    session_id: String,
+) -> Result<(), String> {
+    let removed = sessions
+        .sessions
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(&session_id);
+
+    match removed {
+        Some(_) => {
+            supervisor.kill_group(&session_id);
+            log(
+                &app_handle,
+                ErrorLevel::Info,
+                &format!("Network test {} stopped", session_id),
+            );
+            Ok(())
+        }
+        None => Err(format!("No running network test with id {}", session_id)),
+    }
+}
+
+/// List the running network test sessions
+#[command]
+pub fn list_network_tests(
+    sessions: State<'_, NetworkTestSessions>,
+    supervisor: State<'_, ProcessSupervisor>,
+) -> Result<Vec<NetworkTestSession>, String> {
+    let mut sessions = sessions.sessions.lock().map_err(|e| e.to_string())?;
+    let running = supervisor.running();
+
+    // Forget the sessions whose processes have all exited
+    sessions.retain(|session_id, _| {
+        running
+            .iter()
+            .any(|process| process.group_id.as_deref() == Some(session_id.as_str()))
+    });
+
+    Ok(sessions.values().cloned().collect())
+}
+
+/// Arguments shared by the server and the clients
+fn common_arguments(request: &NetworkTestRequest) -> Vec<String> {
+    let mut arguments = Vec::new();
+    if request.log {
+        arguments.push("-log".to_string());
+    }
+    arguments.extend(request.extra_arguments.iter().cloned());
+    arguments
+}
+
+/// Compute the window rectangle (x, y, width, height) of each client.
+/// Clients are laid out on a grid filling the primary monitor, unless a resolution is forced.
+fn client_layout(
+    app_handle: &AppHandle,
+    client_count: u32,
+    client_resolution: Option<(u32, u32)>,
+) -> Vec<(u32, u32, u32, u32)> {
+    let columns = (client_count as f64).sqrt().ceil() as u32;
+    let rows = client_count.div_ceil(columns);
+
+    let (width, height) = client_resolution.unwrap_or_else(|| {
+        let monitor = app_handle
+            .get_webview_window("main")
+            .and_then(|window| window.primary_monitor().ok().flatten());
+
+        match monitor {
+            Some(monitor) => (monitor.size().width / columns, monitor.size().height / rows),
+            None => DEFAULT_CLIENT_RESOLUTION,
+        }
+    });
+
+    (0..client_count)
+        .map(|index| {
+            let column = index % columns;
+            let row = index / columns;
+            (column * width, row * height, width, height)
+        })
+        .collect()
+}
+
+/// Start an instance of the project, tracked in the group of the session
+fn spawn_instance(
+    app_handle: &AppHandle,
+    supervisor: &ProcessSupervisor,
+    session_id: &str,
+    kind: ProcessKind,
+    engine: &EngineInstall,
+    project_path: &Path,
+    arguments: &[String],
+) -> std::io::Result<RunningProcess> {
+    let editor_path = engine.editor_binary();
+    info!("Starting instance: {} {}", editor_path.display(), arguments.join(" "));
+
+    supervisor.spawn(
+        app_handle,
+        Command::new(&editor_path)
+            .args(arguments)
+            .current_dir(project_path.parent().unwrap()),
+        kind,
+        Some(project_path),
+        Some(session_id.to_string()),
+        engine.version.as_ref().map(EngineVersion::full_version),
+    )
+}
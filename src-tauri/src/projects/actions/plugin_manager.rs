@@ -1,6 +1,7 @@
 use crate::misc::errors::{ErrorLevel, Result};
 use crate::misc::prelude::log;
 use crate::misc::progress::TaskProgress;
+use crate::misc::tasks::TaskKind;
 use crate::projects::models::project::Project;
 use log::error;
 use std::path::PathBuf;
@@ -8,24 +9,27 @@ use tauri::{command, AppHandle};
 
 /// Scan/refresh plugins for specific projects
 #[command]
-pub fn scan_plugins(app_handle: AppHandle, project_paths: Vec<String>) -> Result<()> {
+pub async fn scan_plugins(app_handle: AppHandle, project_paths: Vec<String>) -> Result<()> {
     let task_id = format!("scan_plugins_{}", chrono::Utc::now().timestamp_millis());
-    let progress = TaskProgress::new(
+    let progress = TaskProgress::queued(
         app_handle.clone(),
         task_id,
-        format!("Scanning plugins for {} project(s)", project_paths.len())
-    );
+        format!("Scanning plugins for {} project(s)", project_paths.len()),
+        TaskKind::PluginScan,
+        None,
+    )
+    .await?;
 
     // Convert the project paths from strings to PathBuf
     let paths_to_scan = project_paths
-        .into_iter()
-        .map(PathBuf::from)
+        .iter()
+        .map(|project| Project::resolve_path(&app_handle, project))
         .collect::<Vec<PathBuf>>();
 
     progress.update(0.5, Some("Analyzing plugin directories...".to_string()));
 
     // Refresh the plugins for the specified projects
-    match Project::scan_project_plugins(&app_handle, &paths_to_scan) {
+    match Project::scan_project_plugins(&app_handle, &paths_to_scan, &progress.cancellation_token()) {
         Ok(_) => {
             log(
                 &app_handle,
@@ -42,6 +46,10 @@ pub fn scan_plugins(app_handle: AppHandle, project_paths: Vec<String>) -> Result
             progress.complete(Some("Plugin scanning completed".to_string()));
             Ok(())
         }
+        Err(_) if progress.is_cancelled() => {
+            progress.cancelled(Some("Plugin scanning cancelled".to_string()));
+            Err(progress.cancellation_error())
+        }
         Err(e) => {
             error!("Error refreshing plugins for project(s): {}", e);
             log(
@@ -57,17 +65,20 @@ pub fn scan_plugins(app_handle: AppHandle, project_paths: Vec<String>) -> Result
 
 /// Refresh plugins for all tracked projects
 #[command]
-pub fn refresh_all_plugins(app_handle: AppHandle) -> Result<()> {
+pub async fn refresh_all_plugins(app_handle: AppHandle) -> Result<()> {
     let task_id = format!("refresh_all_plugins_{}", chrono::Utc::now().timestamp_millis());
-    let progress = TaskProgress::new(
+    let progress = TaskProgress::queued(
         app_handle.clone(),
         task_id,
-        "Refreshing plugins for all projects".to_string()
-    );
+        "Refreshing plugins for all projects".to_string(),
+        TaskKind::PluginScan,
+        None,
+    )
+    .await?;
 
     progress.update(0.3, Some("Loading project list...".to_string()));
 
-    match Project::refresh_all_plugins(&app_handle) {
+    match Project::refresh_all_plugins(&app_handle, &progress.cancellation_token()) {
         Ok(_) => {
             log(
                 &app_handle,
@@ -77,6 +88,10 @@ pub fn refresh_all_plugins(app_handle: AppHandle) -> Result<()> {
             progress.complete(Some("All plugins refreshed successfully".to_string()));
             Ok(())
         }
+        Err(_) if progress.is_cancelled() => {
+            progress.cancelled(Some("Plugin refresh cancelled".to_string()));
+            Err(progress.cancellation_error())
+        }
         Err(e) => {
             error!("Error refreshing plugins for all projects: {}", e);
             log(
@@ -0,0 +1,227 @@
+use crate::env;
+use crate::misc::errors::Verror::MessageError;
+use crate::misc::errors::{ErrorLevel, Result};
+use crate::misc::payloads::ProcessExitedPayload;
+use crate::misc::prelude::log;
+use crate::projects::models::process::{ProcessKind, RunningProcess};
+use crate::projects::models::project::Project;
+use crate::projects::models::session::ProjectSession;
+use log::{error, info};
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::{Child, Command};
+use std::sync::Mutex;
+use tauri::{command, AppHandle, Emitter, Manager, State};
+
+/// # Process Supervisor
+/// This module keeps track of every process launched by the application
+/// (editors, game and server instances, IDEs). A background monitor checks
+/// the processes for exit, records the usage sessions of the projects and
+/// notifies the frontend when processes start and stop.
+
+/// Processes launched by the application, keyed by pid
+#[derive(Default)]
+pub struct ProcessSupervisor {
+    processes: Mutex<HashMap<u32, (RunningProcess, Child)>>,
+    pub(crate) sessions: Mutex<()>, // Serializes the updates of the usage sessions in the store
+}
+
+impl ProcessSupervisor {
+    /// Spawn a command and track the resulting process
+    pub fn spawn(
+        &self,
+        app_handle: &AppHandle,
+        command: &mut Command,
+        kind: ProcessKind,
+        project_path: Option<&Path>,
+        group_id: Option<String>,
+        engine_version: Option<String>,
+    ) -> std::io::Result<RunningProcess> {
+        let command_line = std::iter::once(command.get_program())
+            .chain(command.get_args())
+            .map(|part| part.to_string_lossy().to_string())
+            .collect::<Vec<String>>()
+            .join(" ");
+
+        let child = command.spawn()?;
+
+        let process = RunningProcess {
+            pid: child.id(),
+            kind,
+            project_path: project_path.map(|p| p.display().to_string()),
+            project_id: project_path.and_then(|p| Project::find_id(app_handle, p)),
+            group_id,
+            command_line,
+            engine_version,
+            started_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        };
+
+        info!("Tracking process {}: {}", process.pid, process.command_line);
+
+        if let Ok(mut processes) = self.processes.lock() {
+            processes.insert(process.pid, (process.clone(), child));
+        }
+
+        if let Err(e) = app_handle.emit(env::EVENT_PROCESS_STARTED, process.clone()) {
+            error!("Failed to emit process started event: {}", e);
+        }
+        if process.project_path.is_some() {
+            if let Err(e) = Project::emit_project_updated(app_handle) {
+                error!("Failed to emit projects updated event: {}", e);
+            }
+        }
+
+        Ok(process)
+    }
+
+    /// List the tracked processes
+    pub fn running(&self) -> Vec<RunningProcess> {
+        match self.processes.lock() {
+            Ok(processes) => processes.values().map(|(process, _)| process.clone()).collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Kill a tracked process. Its exit is reported by the monitor.
+    pub fn kill(&self, pid: u32) -> Result<()> {
+        let mut processes = self
+            .processes
+            .lock()
+            .map_err(|e| MessageError(e.to_string()))?;
+
+        match processes.get_mut(&pid) {
+            Some((_, child)) => Ok(child.kill()?),
+            None => Err(MessageError(format!("No tracked process with pid {}", pid))),
+        }
+    }
+
+    /// Kill every process of a group, returns the number of processes killed
+    pub fn kill_group(&self, group_id: &str) -> usize {
+        let Ok(mut processes) = self.processes.lock() else {
+            return 0;
+        };
+
+        let mut killed = 0;
+        for (process, child) in processes.values_mut() {
+            if process.group_id.as_deref() != Some(group_id) {
+                continue;
+            }
+
+            match child.kill() {
+                Ok(_) => killed += 1,
+                Err(e) => error!("Failed to kill process {}: {}", process.pid, e),
+            }
+        }
+
+        killed
+    }
+
+    /// Check if a project has a running editor, game or server process
+    pub fn is_project_open(&self, project_path: &Path) -> bool {
+        let project_path = project_path.display().to_string();
+
+        self.running().iter().any(|process| {
+            process.kind != ProcessKind::Ide
+                && process.project_path.as_deref() == Some(project_path.as_str())
+        })
+    }
+
+    /// Remove the processes that have exited and return them with their exit code
+    fn reap(&self) -> Vec<(RunningProcess, Option<i32>)> {
+        let Ok(mut processes) = self.processes.lock() else {
+            return Vec::new();
+        };
+
+        let mut exited = Vec::new();
+        processes.retain(|_, (process, child)| match child.try_wait() {
+            Ok(Some(status)) => {
+                exited.push((process.clone(), status.code()));
+                false
+            }
+            Ok(None) => true,
+            Err(e) => {
+                error!("Failed to check process {}: {}", process.pid, e);
+                exited.push((process.clone(), None));
+                false
+            }
+        });
+
+        exited
+    }
+}
+
+/// Start the background monitor reporting the exit of the tracked processes
+pub fn start_process_monitor(app_handle: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(std::time::Duration::from_millis(env::PROCESS_MONITOR_INTERVAL_MS));
+
+        let exited = app_handle.state::<ProcessSupervisor>().reap();
+        if exited.is_empty() {
+            continue;
+        }
+
+        let ended_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        let mut projects_changed = false;
+        for (process, exit_code) in exited {
+            info!("Process {} exited with code {:?}", process.pid, exit_code);
+            projects_changed |= process.project_path.is_some();
+
+            // Record the usage session of the project
+            if let Some(session) = ProjectSession::from_process(&process, ended_at) {
+                if let Err(e) = ProjectSession::record(&app_handle, session) {
+                    error!("Failed to record the session of process {}: {}", process.pid, e);
+                }
+            }
+
+            if let Err(e) = app_handle.emit(
+                env::EVENT_PROCESS_EXITED,
+                ProcessExitedPayload { process, exit_code },
+            ) {
+                error!("Failed to emit process exited event: {}", e);
+            }
+        }
+
+        if projects_changed {
+            if let Err(e) = Project::emit_project_updated(&app_handle) {
+                error!("Failed to emit projects updated event: {}", e);
+            }
+        }
+    });
+}
+
+/// List the processes launched by the application that are still running
+#[command]
+pub fn list_running_processes(supervisor: State<'_, ProcessSupervisor>) -> Result<Vec<RunningProcess>> {
+    Ok(supervisor.running())
+}
+
+/// Kill a process launched by the application
+#[command]
+pub fn kill_process(
+    app_handle: AppHandle,
+    supervisor: State<'_, ProcessSupervisor>,
+    pid: u32,
+) -> Result<()> {
+    match supervisor.kill(pid) {
+        Ok(_) => {
+            log(&app_handle, ErrorLevel::Info, &format!("Process {} killed", pid));
+            Ok(())
+        }
+        Err(e) => {
+            error!("Failed to kill process {}: {}", pid, e);
+            log(
+                &app_handle,
+                ErrorLevel::Error,
+                &format!("Failed to kill process {}: {}", pid, e),
+            );
+            Err(e)
+        }
+    }
+}
@@ -0,0 +1,302 @@
+use crate::engines::actions::engine_registry;
+use crate::env;
+use crate::misc::errors::Verror::MessageError;
+use crate::misc::errors::{ErrorLevel, Result};
+use crate::misc::payloads::BuildDiagnosticPayload;
+use crate::misc::prelude::log;
+use crate::misc::progress::TaskProgress;
+use crate::misc::tasks::TaskKind;
+use crate::projects::models::build::{host_platform, BuildDiagnostic, BuildReport, BuildRequest, BuildResult};
+use crate::projects::models::project::Project;
+use log::{error, info, warn};
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::sync::mpsc::RecvTimeoutError;
+use std::time::Duration;
+use tauri::{command, AppHandle, Emitter};
+
+/// # Project Builder Actions
+/// This module compiles the C++ code of a project with the UnrealBuildTool of
+/// the engine it is associated with, without opening an IDE. The output of the
+/// tool is streamed line by line through the task progress events, and the
+/// errors and warnings it contains are reported as build diagnostics.
+
+/// Compile a project with UnrealBuildTool
+#[command]
+pub async fn build_project(app_handle: AppHandle, request: BuildRequest) -> Result<BuildResult> {
+    let start_time = std::time::Instant::now();
+    let project_path = Project::resolve_path(&app_handle, &request.project_path);
+
+    if !project_path.exists() {
+        let error_msg = format!("Project file does not exist: {}", project_path.display());
+        error!("{}", error_msg);
+        log(&app_handle, ErrorLevel::Error, &error_msg);
+        return Err(MessageError(error_msg));
+    }
+
+    // Blueprint only projects have no target to compile
+    let project_dir = project_path.parent().unwrap();
+    if !project_dir.join("Source").is_dir() {
+        let error_msg = format!("Project has no C++ code to build: {}", project_path.display());
+        log(&app_handle, ErrorLevel::Error, &error_msg);
+        return Err(MessageError(error_msg));
+    }
+
+    let engine = engine_registry::read_engine_association(&project_path)
+        .and_then(|association| engine_registry::resolve_engine(&app_handle, &association, &project_path))
+        .inspect_err(|e| log(&app_handle, ErrorLevel::Error, &e.to_string()))?;
+
+    let build_script = engine.build_script();
+    if !build_script.exists() {
+        let error_msg = format!(
+            "Build script does not exist: {} (engine {})",
+            build_script.display(),
+            engine.name
+        );
+        log(&app_handle, ErrorLevel::Error, &error_msg);
+        return Err(MessageError(error_msg));
+    }
+
+    let project_name = project_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("Unknown");
+    let target_name = request.target.target_name(project_name);
+    let platform = request
+        .platform
+        .clone()
+        .filter(|platform| !platform.is_empty())
+        .unwrap_or_else(|| host_platform().to_string());
+
+    let mut arguments = vec![
+        target_name.clone(),
+        platform.clone(),
+        request.configuration.as_str().to_string(),
+        format!("-Project={}", project_path.display()),
+        "-WaitMutex".to_string(),
+    ];
+    arguments.extend(request.extra_arguments.iter().cloned());
+
+    let command_line = format!("{} {}", build_script.display(), arguments.join(" "));
+
+    let task_id = format!("build_project_{}", chrono::Utc::now().timestamp_millis());
+    let progress = TaskProgress::queued(
+        app_handle.clone(),
+        task_id.clone(),
+        format!(
+            "Building {} ({} {})",
+            target_name,
+            platform,
+            request.configuration.as_str()
+        ),
+        TaskKind::Build,
+        Some(project_path.display().to_string()),
+    )
+    .await?;
+
+    info!("Running UnrealBuildTool: {}", command_line);
+    log(
+        &app_handle,
+        ErrorLevel::Info,
+        &format!(
+            "Building {} for {} in {} with {}",
+            target_name,
+            platform,
+            request.configuration.as_str(),
+            engine.name
+        ),
+    );
+
+    let mut command = Command::new(&build_script);
+    command
+        .args(&arguments)
+        .current_dir(&engine.root)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    // The build script runs in its own process group, so that the whole tree can be killed on cancellation
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            let error_msg = format!("Failed to start UnrealBuildTool: {}", e);
+            log(&app_handle, ErrorLevel::Error, &error_msg);
+            progress.fail(Some(error_msg.clone()));
+            return Err(MessageError(error_msg));
+        }
+    };
+
+    // Step 1: Forward the output of UBT line by line, stdout and stderr being read on their own threads
+    let (sender, receiver) = mpsc::channel::<String>();
+    let readers = [
+        child.stdout.take().map(|stdout| spawn_line_reader(stdout, sender.clone())),
+        child.stderr.take().map(|stderr| spawn_line_reader(stderr, sender.clone())),
+    ];
+    drop(sender);
+
+    let mut build_progress = 0.0;
+    let mut report = BuildReport::default();
+    loop {
+        let line = match receiver.recv_timeout(Duration::from_millis(env::TASK_CANCELLATION_POLL_INTERVAL_MS)) {
+            Ok(line) => line,
+            Err(RecvTimeoutError::Timeout) => {
+                // The build script only wraps UBT, which would keep the output open if only the script was killed
+                if progress.is_cancelled() {
+                    if let Err(e) = kill_process_tree(&mut child) {
+                        error!("Failed to stop UnrealBuildTool: {}", e);
+                    }
+                    break;
+                }
+                continue;
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+
+        info!("[UBT] {}", line);
+        if let Some(action_progress) = parse_action_progress(&line) {
+            build_progress = action_progress;
+        }
+
+        // Report each new error or warning as soon as it is printed
+        if let Some(diagnostic) = BuildDiagnostic::parse(&line) {
+            if report.add(diagnostic.clone()) {
+                let payload = BuildDiagnosticPayload {
+                    task_id: task_id.clone(),
+                    project_path: project_path.display().to_string(),
+                    diagnostic,
+                };
+                if let Err(e) = app_handle.emit(env::EVENT_BUILD_DIAGNOSTIC, payload) {
+                    error!("Failed to emit build diagnostic event: {}", e);
+                }
+            }
+        }
+
+        progress.update(build_progress, Some(line));
+    }
+
+    // A process surviving the cancellation could keep the output open, the readers are then left behind
+    if !progress.is_cancelled() {
+        for reader in readers.into_iter().flatten() {
+            let _ = reader.join();
+        }
+    }
+
+    // Step 2: Wait for UBT to exit
+    let status = match child.wait() {
+        Ok(status) => status,
+        Err(e) => {
+            let error_msg = format!("Failed to wait for UnrealBuildTool: {}", e);
+            log(&app_handle, ErrorLevel::Error, &error_msg);
+            progress.fail(Some(error_msg.clone()));
+            return Err(MessageError(error_msg));
+        }
+    };
+
+    if progress.is_cancelled() {
+        let cancel_msg = format!("Build of {} cancelled", target_name);
+        log(&app_handle, ErrorLevel::Warning, &cancel_msg);
+        progress.cancelled(Some(cancel_msg));
+        return Err(progress.cancellation_error());
+    }
+
+    let duration = start_time.elapsed();
+    let result = BuildResult {
+        project_path: project_path.display().to_string(),
+        target_name: target_name.clone(),
+        platform,
+        configuration: request.configuration.clone(),
+        success: status.success(),
+        exit_code: status.code(),
+        duration_ms: duration.as_millis(),
+        command_line,
+        report,
+    };
+
+    if result.success {
+        let success_msg = format!(
+            "{} built successfully in {:.1}s ({} warning(s))",
+            target_name,
+            duration.as_secs_f64(),
+            result.report.warning_count
+        );
+        log(&app_handle, ErrorLevel::Info, &success_msg);
+        progress.complete(Some(success_msg));
+    } else {
+        let error_msg = match result.exit_code {
+            Some(code) => format!(
+                "Build of {} failed with exit code {} ({} error(s), {} warning(s))",
+                target_name, code, result.report.error_count, result.report.warning_count
+            ),
+            None => format!("Build of {} has been terminated", target_name),
+        };
+        warn!("{}", error_msg);
+        log(&app_handle, ErrorLevel::Error, &error_msg);
+        progress.fail(Some(error_msg));
+    }
+
+    Ok(result)
+}
+
+/// Kill a process with every process it has started (UBT, the compilers, ...)
+fn kill_process_tree(child: &mut Child) -> std::io::Result<()> {
+    #[cfg(unix)]
+    let status = Command::new("kill")
+        .args(["-KILL", "--", &format!("-{}", child.id())])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+    #[cfg(windows)]
+    let status = {
+        use std::os::windows::process::CommandExt;
+        Command::new("taskkill")
+            .args(["/T", "/F", "/PID", &child.id().to_string()])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .creation_flags(env::WINDOWS_CREATE_NO_WINDOW)
+            .status()
+    };
+
+    match status {
+        Ok(status) if status.success() => Ok(()),
+        // The tree could not be killed, at least stop the build script
+        Ok(status) => {
+            warn!("Failed to kill the process tree of {} ({})", child.id(), status);
+            child.kill()
+        }
+        Err(e) => {
+            warn!("Failed to kill the process tree of {}: {}", child.id(), e);
+            child.kill()
+        }
+    }
+}
+
+/// Read a stream line by line on a new thread and send the lines to the channel
+fn spawn_line_reader<R: Read + Send + 'static>(
+    stream: R,
+    sender: mpsc::Sender<String>,
+) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        // UBT output is not guaranteed to be valid UTF-8 (localized compiler messages)
+        for line in BufReader::new(stream).split(b'\n').map_while(|line| line.ok()) {
+            let line = String::from_utf8_lossy(&line).trim_end().to_string();
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    })
+}
+
+/// Parse the "[current/total]" prefix UBT prints before each action, returns the completed ratio
+fn parse_action_progress(line: &str) -> Option<f32> {
+    let counter = line.trim_start().strip_prefix('[')?.split(']').next()?;
+    let (current, total) = counter.split_once('/')?;
+    let (current, total) = (current.trim().parse::<f32>().ok()?, total.trim().parse::<f32>().ok()?);
+
+    if total > 0.0 {
+        Some(current / total)
+    } else {
+        None
+    }
+}
@@ -2,6 +2,8 @@ use crate::misc::errors::Verror::MessageError;
 use crate::misc::errors::{ErrorLevel, Result};
 use crate::misc::prelude::{format_size, log};
 use crate::misc::progress::TaskProgress;
+use crate::misc::tasks::TaskKind;
+use crate::projects::actions::project_lock;
 use crate::projects::models::project::Project;
 use crate::settings::actions::settings_manager;
 use log::{error, info};
@@ -39,8 +41,9 @@ pub async fn clean_project(
     app_handle: AppHandle,
     project_path: String,
     selection: CleaningSelection,
+    force: Option<bool>,
 ) -> Result<CleaningResult> {
-    let project_path = PathBuf::from(project_path);
+    let project_path = Project::resolve_path(&app_handle, &project_path);
 
     if !project_path.exists() {
         let error_msg = format!("Project file does not exist: {}", project_path.display());
@@ -49,22 +52,47 @@ pub async fn clean_project(
         return Err(MessageError(error_msg));
     }
 
-    let project_dir = project_path.parent().unwrap();
     let project_name = project_path
         .file_stem()
         .and_then(|s| s.to_str())
         .unwrap_or("Unknown");
 
     let task_id = format!("clean_project_{}", chrono::Utc::now().timestamp_millis());
-    let progress = TaskProgress::new(
+    let progress = TaskProgress::queued(
         app_handle.clone(),
         task_id,
-        format!("Cleaning project: {}", project_name)
-    );
+        format!("Cleaning project: {}", project_name),
+        TaskKind::Clean,
+        Some(project_path.display().to_string()),
+    )
+    .await?;
+
+    // Never delete files an editor may be using, unless explicitly forced.
+    // Checked once the task leaves the queue, an editor may have been opened while it waited.
+    if let Err(e) = project_lock::ensure_project_unlocked(&app_handle, &project_path, force.unwrap_or(false)) {
+        progress.fail(Some(e.to_string()));
+        return Err(e);
+    }
+
+    clean_project_files(&app_handle, &project_path, &selection, &progress)
+}
+
+/// Clean the files of a project, reporting to a task or to a sub-task of a bigger operation
+pub fn clean_project_files(
+    app_handle: &AppHandle,
+    project_path: &Path,
+    selection: &CleaningSelection,
+    progress: &TaskProgress,
+) -> Result<CleaningResult> {
+    let project_dir = project_path.parent().unwrap();
+    let project_name = project_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("Unknown");
 
     info!("Starting cleaning process for project: {}", project_name);
     log(
-        &app_handle,
+        app_handle,
         ErrorLevel::Info,
         &format!("Starting cleaning process for project: {}", project_name),
     );
@@ -78,34 +106,35 @@ pub async fn clean_project(
 
     progress.update(0.2, Some("Cleaning project directories...".to_string()));
 
+    // List the directories to clean, as (parent directory, directory name)
+    let mut targets: Vec<(PathBuf, &str)> = Vec::new();
+
     // Clean project-level directories
     if selection.ide_files {
-        clean_directory(project_dir, ".vs", &mut cleaned_items);
-        clean_directory(project_dir, ".idea", &mut cleaned_items);
+        targets.push((project_dir.to_path_buf(), ".vs"));
+        targets.push((project_dir.to_path_buf(), ".idea"));
     }
 
     if selection.binaries {
-        clean_directory(project_dir, "Binaries", &mut cleaned_items);
+        targets.push((project_dir.to_path_buf(), "Binaries"));
     }
 
     if selection.build {
-        clean_directory(project_dir, "Build", &mut cleaned_items);
+        targets.push((project_dir.to_path_buf(), "Build"));
     }
 
     if selection.intermediate {
-        clean_directory(project_dir, "Intermediate", &mut cleaned_items);
+        targets.push((project_dir.to_path_buf(), "Intermediate"));
     }
 
     if selection.derived_data_cache {
-        clean_directory(project_dir, "DerivedDataCache", &mut cleaned_items);
+        targets.push((project_dir.to_path_buf(), "DerivedDataCache"));
     }
 
     if selection.saved {
-        clean_directory(project_dir, "Saved", &mut cleaned_items);
+        targets.push((project_dir.to_path_buf(), "Saved"));
     }
 
-    progress.update(0.6, Some("Cleaning plugin directories...".to_string()));
-
     // Clean plugin directories if requested
     if selection.analyze_plugins {
         let plugins_dir = project_dir.join("Plugins");
@@ -116,15 +145,15 @@ pub async fn clean_project(
                         let plugin_dir = entry.path();
 
                         if selection.plugin_binaries {
-                            clean_directory(&plugin_dir, "Binaries", &mut cleaned_items);
+                            targets.push((plugin_dir.clone(), "Binaries"));
                         }
 
                         if selection.plugin_intermediate {
-                            clean_directory(&plugin_dir, "Intermediate", &mut cleaned_items);
+                            targets.push((plugin_dir.clone(), "Intermediate"));
                         }
 
                         if selection.plugin_node_size_cache {
-                            clean_directory(&plugin_dir, "NodeSizeCache", &mut cleaned_items);
+                            targets.push((plugin_dir.clone(), "NodeSizeCache"));
                         }
                     }
                 }
@@ -132,14 +161,38 @@ pub async fn clean_project(
         }
     }
 
+    let total_targets = targets.len();
+    for (index, (base_dir, dir_name)) in targets.iter().enumerate() {
+        // Stop between two directories: the ones already deleted stay deleted
+        if let Err(e) = progress.check_cancelled() {
+            let new_size = fs_extra::dir::get_size(project_dir).unwrap_or(0);
+            if let Err(e) = update_project_size(app_handle, project_path, new_size) {
+                error!("Failed to update project size: {}", e);
+            }
+
+            let cancel_msg = format!(
+                "Cleaning cancelled after {} of {} items",
+                cleaned_items.len(),
+                total_targets
+            );
+            log(app_handle, ErrorLevel::Warning, &cancel_msg);
+            progress.cancelled(Some(cancel_msg));
+            return Err(e);
+        }
+
+        let clean_progress = 0.2 + (index as f32 / total_targets as f32) * 0.6;
+        progress.update(clean_progress, Some(format!("Cleaning {}/{}...", base_dir.display(), dir_name)));
+        clean_directory(base_dir, dir_name, &mut cleaned_items);
+    }
+
     progress.update(0.8, Some("Saving settings...".to_string()));
 
     // Save as default if requested
     if selection.save_as_default {
-        if let Err(e) = save_cleaning_defaults(&app_handle, &selection) {
+        if let Err(e) = save_cleaning_defaults(app_handle, selection) {
             error!("Failed to save cleaning defaults: {}", e);
             log(
-                &app_handle,
+                app_handle,
                 ErrorLevel::Error,
                 &format!("Failed to save cleaning defaults: {}", e),
             );
@@ -161,13 +214,11 @@ pub async fn clean_project(
         project_name, new_size_str, saved_size_str
     );
 
-    progress.complete(Some(format!("Cleaned {} items, saved {}", cleaned_items.len(), saved_size_str)));
-
     info!("{}", completion_msg);
-    log(&app_handle, ErrorLevel::Info, &completion_msg);
+    log(app_handle, ErrorLevel::Info, &completion_msg);
 
     // Update project size in store
-    if let Err(e) = update_project_size(&app_handle, &project_path, new_size) {
+    if let Err(e) = update_project_size(app_handle, project_path, new_size) {
         error!("Failed to update project size: {}", e);
     }
 
@@ -1,14 +1,19 @@
+use crate::engines::actions::engine_registry;
+use crate::env;
 use crate::misc::errors::{ErrorLevel, Result};
 use crate::misc::prelude::{format_size, log};
 use crate::misc::progress::TaskProgress;
-use crate::projects::actions::project_cleaner::{CleaningSelection, clean_project};
+use crate::misc::tasks::TaskKind;
+use crate::projects::actions::{archive_reader, archive_volumes, archive_writer};
+use crate::projects::actions::project_cleaner::{CleaningSelection, clean_project_files};
+use crate::projects::actions::project_lock;
+use crate::projects::models::archive::{ArchiveManifest, ArchiveRules};
 use crate::projects::models::project::Project;
 use crate::settings::actions::settings_manager;
 use log::{error};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::process::Command;
 use tauri::{command, AppHandle};
 use crate::misc::errors::Verror::MessageError;
 
@@ -19,6 +24,18 @@ pub struct CompressionRequest {
     pub compression_algorithm: CompressionAlgorithm,
     pub clean_before_compress: bool,
     pub cleaning_selection: Option<CleaningSelection>,
+    #[serde(default)]
+    pub force: bool, // Compress (and clean) the project even if it is currently open
+    #[serde(default)]
+    pub rules: ArchiveRules, // Files to archive (the whole project by default)
+    #[serde(default)]
+    pub write_manifest_file: bool, // Also write the manifest next to the archive (it is always embedded)
+    #[serde(default)]
+    pub max_volume_size: Option<u64>, // Split the archive into numbered volumes of at most this size in bytes
+    #[serde(default)]
+    pub backup_parent: Option<String>, // Only archive the changes since this archive (the last backup of the chain)
+    #[serde(default)]
+    pub options: CompressionOptions, // Compression level, threads and store only mode (format defaults if not set)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,6 +44,19 @@ pub enum CompressionAlgorithm {
     SevenZip,
     Tar,
     TarGz,
+    ZipZstd, // Zip with Zstandard compressed entries
+    TarZst,  // Tar compressed with Zstandard
+}
+
+/// Tuning of the compression of an archive
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CompressionOptions {
+    #[serde(default)]
+    pub level: Option<i32>,     // 0 to 9 for deflate, gzip and 7z, 1 to 22 for Zstandard (default of the format if not set)
+    #[serde(default)]
+    pub threads: Option<usize>, // Compression threads, for zip (one file per thread) and tar.zst (every core if not set)
+    #[serde(default)]
+    pub store_only: bool,       // Store the files without compressing them, for content that is already compressed (zip and tar)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,6 +66,74 @@ pub struct CompressionResult {
     pub compressed_size: u64,
     pub compression_ratio: f64,
     pub duration_ms: u128,
+    pub file_count: usize,             // Number of archived files
+    pub manifest_path: Option<String>, // Manifest written next to the archive (None if not requested)
+    #[serde(default)]
+    pub volume_paths: Vec<String>,     // Volumes of a split archive, in order (empty if not split)
+    #[serde(default)]
+    pub options: CompressionOptions,   // Options the archive has been written with, defaults included
+}
+
+impl CompressionOptions {
+    /// Check the options against an algorithm and fill in the defaults, as the archive is written with.
+    /// The level is not set for the archives without compression, and formats that cannot be
+    /// compressed in parallel use a single thread.
+    pub fn resolve(&self, algorithm: &CompressionAlgorithm) -> Result<CompressionOptions> {
+        if self.threads == Some(0) {
+            return Err(MessageError("At least one compression thread is required".to_string()));
+        }
+        let threads = match algorithm {
+            CompressionAlgorithm::Zip | CompressionAlgorithm::ZipZstd | CompressionAlgorithm::TarZst => self
+                .threads
+                .unwrap_or_else(|| std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)),
+            _ => 1,
+        };
+
+        if self.store_only {
+            return match algorithm {
+                CompressionAlgorithm::Zip | CompressionAlgorithm::ZipZstd | CompressionAlgorithm::Tar => {
+                    Ok(CompressionOptions {
+                        level: None,
+                        threads: Some(threads),
+                        store_only: true,
+                    })
+                }
+                _ => Err(MessageError(format!(
+                    "{} archives are always compressed, use ZIP or TAR to only store the files",
+                    get_algorithm_name(algorithm)
+                ))),
+            };
+        }
+
+        let (levels, default_level) = match algorithm {
+            CompressionAlgorithm::Zip | CompressionAlgorithm::TarGz => (0..=9, env::ARCHIVE_DEFLATE_LEVEL),
+            CompressionAlgorithm::SevenZip => (0..=9, env::ARCHIVE_LZMA_PRESET),
+            CompressionAlgorithm::ZipZstd | CompressionAlgorithm::TarZst => (1..=22, env::ARCHIVE_ZSTD_LEVEL),
+            CompressionAlgorithm::Tar => {
+                return Ok(CompressionOptions {
+                    level: None,
+                    threads: Some(threads),
+                    store_only: false,
+                })
+            }
+        };
+        let level = self.level.unwrap_or(default_level);
+        if !levels.contains(&level) {
+            return Err(MessageError(format!(
+                "Invalid compression level {} for {} archives (from {} to {})",
+                level,
+                get_algorithm_name(algorithm),
+                levels.start(),
+                levels.end()
+            )));
+        }
+
+        Ok(CompressionOptions {
+            level: Some(level),
+            threads: Some(threads),
+            store_only: false,
+        })
+    }
 }
 
 /// Get system username
@@ -57,7 +155,7 @@ pub async fn compress_project(
     request: CompressionRequest,
 ) -> Result<CompressionResult> {
     let start_time = std::time::Instant::now();
-    let project_path = PathBuf::from(&request.project_path);
+    let project_path = Project::resolve_path(&app_handle, &request.project_path);
     
     if !project_path.exists() {
         let error_msg = format!("Project file does not exist: {}", project_path.display());
@@ -65,6 +163,16 @@ pub async fn compress_project(
         log(&app_handle, ErrorLevel::Error, &error_msg);
         return Err(MessageError(error_msg));
     }
+
+    // The options the archive is written with, the defaults of the format filled in
+    let options = match request.options.resolve(&request.compression_algorithm) {
+        Ok(options) => options,
+        Err(e) => {
+            error!("{}", e);
+            log(&app_handle, ErrorLevel::Error, &e.to_string());
+            return Err(e);
+        }
+    };
     
     let project_dir = project_path.parent().unwrap();
     let project_name = project_path.file_stem()
@@ -72,24 +180,41 @@ pub async fn compress_project(
         .unwrap_or("Unknown");
     
     let task_id = format!("compress_project_{}", chrono::Utc::now().timestamp_millis());
-    let progress = TaskProgress::new(
+    let progress = TaskProgress::queued(
         app_handle.clone(),
         task_id,
-        format!("Compressing project: {}", project_name)
-    );
+        format!("Compressing project: {}", project_name),
+        TaskKind::Compress,
+        Some(project_path.display().to_string()),
+    )
+    .await?;
+
+    // An open project may be modified while it is archived.
+    // Checked once the task leaves the queue, an editor may have been opened while it waited.
+    if let Err(e) = project_lock::ensure_project_unlocked(&app_handle, &project_path, request.force) {
+        progress.fail(Some(e.to_string()));
+        return Err(e);
+    }
     
     log(&app_handle, ErrorLevel::Info, &format!("Starting compression process for project: {}", project_name));
     
     // Clean the project if requested
+    let cleaned = request.clean_before_compress && request.cleaning_selection.is_some();
     if request.clean_before_compress {
         if let Some(cleaning_selection) = request.cleaning_selection {
             progress.update(0.1, Some("Cleaning project before compression...".to_string()));
             log(&app_handle, ErrorLevel::Info, "Cleaning project before compression...");
             
-            match clean_project(app_handle.clone(), request.project_path.clone(), cleaning_selection).await {
+            // The cleaning reports into the 10% to 30% range of the compression task
+            let cleaning_progress = progress.sub_task(0.1, 0.2, "Cleaning");
+            match clean_project_files(&app_handle, &project_path, &cleaning_selection, &cleaning_progress) {
                 Ok(_) => {
                     log(&app_handle, ErrorLevel::Info, "Project cleaned successfully before compression");
                 }
+                Err(e) if progress.is_cancelled() => {
+                    progress.cancelled(Some("Compression cancelled".to_string()));
+                    return Err(e);
+                }
                 Err(e) => {
                     let error_msg = format!("Failed to clean project before compression: {}", e);
                     log(&app_handle, ErrorLevel::Error, &error_msg);
@@ -100,27 +225,132 @@ pub async fn compress_project(
         }
     }
     
-    progress.update(0.3, Some("Calculating project size...".to_string()));
-    
-    // Get the original size
-    let original_size = fs_extra::dir::get_size(project_dir).unwrap_or(0);
+    if let Err(e) = progress.check_cancelled() {
+        progress.cancelled(Some("Compression cancelled".to_string()));
+        return Err(e);
+    }
+
+    progress.update(0.3, Some("Listing project files...".to_string()));
     
     // Generate output filename using user's format
     let output_filename = generate_filename(&app_handle, &project_path, &request.compression_algorithm)?;
     let output_path = PathBuf::from(&request.destination_path).join(output_filename);
     
-    log(&app_handle, ErrorLevel::Info, &format!("Compressing to: {}", output_path.display()));
+    // The archive may be written inside the project directory, it must not archive itself (nor its volumes)
+    let entries = match archive_writer::collect_entries(project_dir, &request.rules, &progress.cancellation_token()) {
+        Ok(entries) => entries
+            .into_iter()
+            .filter(|entry| {
+                entry.source_path != output_path
+                    && archive_volumes::volume_base(&entry.source_path).as_deref() != Some(output_path.as_path())
+            })
+            .collect::<Vec<_>>(),
+        Err(e) => {
+            if progress.is_cancelled() {
+                progress.cancelled(Some("Compression cancelled".to_string()));
+                return Err(progress.cancellation_error());
+            }
+            let error_msg = format!("Failed to list project files: {}", e);
+            log(&app_handle, ErrorLevel::Error, &error_msg);
+            progress.fail(Some(error_msg.clone()));
+            return Err(MessageError(error_msg));
+        }
+    };
+    
+    // A differential backup only archives the files changed since its parent
+    let mut backup_diff = None;
+    let entries = match &request.backup_parent {
+        Some(parent_path) => {
+            progress.update(0.3, Some("Reading the previous backup...".to_string()));
+            let parent_progress = progress.sub_task(0.3, 0.1, "Reading the previous backup");
+            match load_backup_parent(Path::new(parent_path), &output_path, project_name, &parent_progress) {
+                Ok((parent_name, parent_manifest)) => {
+                    let diff = archive_writer::diff_entries(entries, &parent_manifest);
+                    log(
+                        &app_handle,
+                        ErrorLevel::Info,
+                        &format!(
+                            "Differential backup on top of {}: {} unchanged and {} deleted files",
+                            parent_name,
+                            diff.unchanged.len(),
+                            diff.deleted.len()
+                        ),
+                    );
+                    let entries = diff.entries;
+                    backup_diff = Some((parent_name, diff.unchanged, diff.deleted));
+                    entries
+                }
+                Err(_) if progress.is_cancelled() => {
+                    progress.cancelled(Some("Compression cancelled".to_string()));
+                    return Err(progress.cancellation_error());
+                }
+                Err(e) => {
+                    let error_msg = format!("Failed to read the previous backup: {}", e);
+                    log(&app_handle, ErrorLevel::Error, &error_msg);
+                    progress.fail(Some(error_msg.clone()));
+                    return Err(MessageError(error_msg));
+                }
+            }
+        }
+        None => entries,
+    };
+    
+    // Get the original size
+    let original_size: u64 = entries.iter().map(|entry| entry.size).sum();
+    
+    log(&app_handle, ErrorLevel::Info, &format!("Compressing {} files to: {}", entries.len(), output_path.display()));
+    log(&app_handle, ErrorLevel::Info, &format!("Compression options: {}", describe_options(&options)));
     
     progress.update(0.4, Some(format!("Creating {} archive...", get_algorithm_name(&request.compression_algorithm))));
     
     // Perform compression based on the selected algorithm
-    match compress_directory(project_dir, &output_path, &request.compression_algorithm, &progress) {
-        Ok(_) => {
+    // The archive writing reports into the 40% to 90% range of the compression task
+    let archive_progress = progress.sub_task(0.4, 0.5, "Archiving");
+    let mut manifest = ArchiveManifest::for_project(
+        &project_path,
+        &request.compression_algorithm,
+        &request.rules,
+        request.max_volume_size,
+        cleaned,
+    );
+    if let Some((parent_name, unchanged, deleted)) = backup_diff {
+        manifest.parent = Some(parent_name);
+        manifest.unchanged = unchanged;
+        manifest.deleted = deleted;
+    }
+    match archive_writer::write_archive(
+        &entries,
+        &output_path,
+        &request.compression_algorithm,
+        &options,
+        request.max_volume_size,
+        &mut manifest,
+        &archive_progress,
+    ) {
+        Ok(written_paths) => {
             progress.update(0.9, Some("Finalizing compression...".to_string()));
             
-            let compressed_size = fs::metadata(&output_path)
-                .map(|m| m.len())
-                .unwrap_or(0);
+            // The sidecar manifest is a convenience, the archive is valid without it
+            let manifest_path = if request.write_manifest_file {
+                let manifest_path = ArchiveManifest::sidecar_path(&output_path);
+                match serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())
+                    .and_then(|data| fs::write(&manifest_path, data).map_err(|e| e.to_string()))
+                {
+                    Ok(_) => Some(manifest_path.to_string_lossy().to_string()),
+                    Err(e) => {
+                        error!("Failed to write the manifest {}: {}", manifest_path.display(), e);
+                        log(&app_handle, ErrorLevel::Warning, &format!("Failed to write the archive manifest: {}", e));
+                        None
+                    }
+                }
+            } else {
+                None
+            };
+            
+            let compressed_size = written_paths
+                .iter()
+                .map(|path| fs::metadata(path).map(|m| m.len()).unwrap_or(0))
+                .sum::<u64>();
             
             let compression_ratio = if original_size > 0 {
                 (compressed_size as f64 / original_size as f64) * 100.0
@@ -130,12 +360,25 @@ pub async fn compress_project(
             
             let duration = start_time.elapsed();
             
+            // A split archive is opened from its first volume
             let result = CompressionResult {
-                output_path: output_path.to_string_lossy().to_string(),
+                output_path: written_paths
+                    .first()
+                    .unwrap_or(&output_path)
+                    .to_string_lossy()
+                    .to_string(),
                 original_size,
                 compressed_size,
                 compression_ratio,
                 duration_ms: duration.as_millis(),
+                file_count: manifest.files.len(),
+                manifest_path,
+                volume_paths: if request.max_volume_size.is_some() {
+                    written_paths.iter().map(|path| path.to_string_lossy().to_string()).collect()
+                } else {
+                    Vec::new()
+                },
+                options,
             };
             
             let completion_msg = format!(
@@ -151,6 +394,12 @@ pub async fn compress_project(
             
             Ok(result)
         }
+        // The archive writer removes the partial archive itself
+        Err(e) if progress.is_cancelled() => {
+            log(&app_handle, ErrorLevel::Warning, &format!("Compression of {} cancelled: {}", project_name, e));
+            progress.cancelled(Some("Compression cancelled".to_string()));
+            Err(progress.cancellation_error())
+        }
         Err(e) => {
             let error_msg = format!("Failed to compress project: {}", e);
             log(&app_handle, ErrorLevel::Error, &error_msg);
@@ -160,6 +409,66 @@ pub async fn compress_project(
     }
 }
 
+/// Read the manifest of the archive a differential backup applies to, and check that the backup can
+/// be chained to it. Returns the file name the backup refers to its parent with.
+fn load_backup_parent(
+    parent_path: &Path,
+    output_path: &Path,
+    project_name: &str,
+    progress: &TaskProgress,
+) -> Result<(String, ArchiveManifest)> {
+    if archive_volumes::archive_files(parent_path).is_empty() {
+        return Err(MessageError(format!("Archive does not exist: {}", parent_path.display())));
+    }
+
+    // The chain is restored from the directory of its last backup, its archives must stay together
+    let parent_path = archive_volumes::volume_base(parent_path).unwrap_or_else(|| parent_path.to_path_buf());
+    let same_directory = match (parent_path.parent(), output_path.parent()) {
+        (Some(parent_dir), Some(output_dir)) => {
+            fs::canonicalize(parent_dir).ok() == fs::canonicalize(output_dir).ok()
+        }
+        _ => false,
+    };
+    if !same_directory {
+        return Err(MessageError(
+            "A differential backup must be written in the same directory as the previous backup".to_string(),
+        ));
+    }
+
+    if parent_path == output_path {
+        return Err(MessageError(format!(
+            "The backup would replace the previous backup {}, change the filename format",
+            parent_path.display()
+        )));
+    }
+
+    let manifest = archive_reader::read_manifest(&parent_path, progress)?;
+    if manifest.project_name != project_name {
+        return Err(MessageError(format!(
+            "{} is a backup of {}, not of {}",
+            parent_path.display(),
+            manifest.project_name,
+            project_name
+        )));
+    }
+
+    let parent_name = parent_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    Ok((parent_name, manifest))
+}
+
+/// Describe the compression options for the logs
+fn describe_options(options: &CompressionOptions) -> String {
+    let compression = match options.level {
+        _ if options.store_only => "store only".to_string(),
+        Some(level) => format!("level {}", level),
+        None => "no compression".to_string(),
+    };
+    format!("{}, {} thread(s)", compression, options.threads.unwrap_or(1))
+}
+
 /// Generate filename based on user's format template
 fn generate_filename(
     app_handle: &AppHandle,
@@ -211,10 +520,18 @@ fn generate_filename(
         replacements.insert("ProjectType".to_string(), project_type.to_string()); // Alternative
         
         // Engine version
-        let engine_version = match project.engine_association {
+        let engine_version = match &project.engine_association {
             crate::projects::models::project::EngineAssociation::Standard(version) => {
                 version.replace(".", "-")
             }
+            // Source builds are named after the version of the registered engine, if any
+            crate::projects::models::project::EngineAssociation::Guid(guid) => {
+                engine_registry::resolve_engine(app_handle, &project.engine_association, project_path)
+                    .ok()
+                    .and_then(|engine| engine.version)
+                    .map(|version| version.short_version().replace(".", "-"))
+                    .unwrap_or_else(|| crate::projects::models::project::EngineAssociation::normalize_guid(guid))
+            }
             crate::projects::models::project::EngineAssociation::Custom => "Custom".to_string(),
         };
         replacements.insert("Engine".to_string(), engine_version.clone());
@@ -293,135 +610,14 @@ fn sanitize_filename(filename: &str) -> String {
     result
 }
 
-fn compress_directory(
-    source_dir: &Path,
-    output_path: &Path,
-    algorithm: &CompressionAlgorithm,
-    progress: &TaskProgress,
-) -> std::result::Result<(), Box<dyn std::error::Error>> {
-    match algorithm {
-        CompressionAlgorithm::Zip => {
-            progress.update(0.5, Some("Creating ZIP archive...".to_string()));
-            compress_with_zip(source_dir, output_path)
-        },
-        CompressionAlgorithm::SevenZip => {
-            progress.update(0.5, Some("Creating 7-Zip archive...".to_string()));
-            compress_with_7zip(source_dir, output_path)
-        },
-        CompressionAlgorithm::Tar => {
-            progress.update(0.5, Some("Creating TAR archive...".to_string()));
-            compress_with_tar(source_dir, output_path, false)
-        },
-        CompressionAlgorithm::TarGz => {
-            progress.update(0.5, Some("Creating TAR.GZ archive...".to_string()));
-            compress_with_tar(source_dir, output_path, true)
-        },
-    }
-}
-
-fn compress_with_zip(
-    source_dir: &Path,
-    output_path: &Path,
-) -> std::result::Result<(), Box<dyn std::error::Error>> {
-    // Use the system zip command if available
-    if cfg!(target_os = "windows") {
-        // On Windows, try PowerShell Compress-Archive
-        let result = Command::new("powershell")
-            .args([
-                "-Command",
-                &format!(
-                    "Compress-Archive -Path '{}\\*' -DestinationPath '{}'",
-                    source_dir.display(),
-                    output_path.display()
-                ),
-            ])
-            .output()?;
-        
-        if !result.status.success() {
-            return Err(format!(
-                "PowerShell compression failed: {}",
-                String::from_utf8_lossy(&result.stderr)
-            ).into());
-        }
-    } else {
-        // On Unix systems, use the zip command
-        let result = Command::new("zip")
-            .args([
-                "-r",
-                output_path.to_str().unwrap(),
-                ".",
-            ])
-            .current_dir(source_dir)
-            .output()?;
-        
-        if !result.status.success() {
-            return Err(format!(
-                "Zip compression failed: {}",
-                String::from_utf8_lossy(&result.stderr)
-            ).into());
-        }
-    }
-    
-    Ok(())
-}
-
-fn compress_with_7zip(
-    source_dir: &Path,
-    output_path: &Path,
-) -> std::result::Result<(), Box<dyn std::error::Error>> {
-    let result = Command::new("7z")
-        .args([
-            "a",
-            "-t7z",
-            output_path.to_str().unwrap(),
-            &format!("{}/*", source_dir.display()),
-        ])
-        .output()?;
-    
-    if !result.status.success() {
-        return Err(format!(
-            "7-Zip compression failed: {}",
-            String::from_utf8_lossy(&result.stderr)
-        ).into());
-    }
-    
-    Ok(())
-}
-
-fn compress_with_tar(
-    source_dir: &Path,
-    output_path: &Path,
-    use_gzip: bool,
-) -> std::result::Result<(), Box<dyn std::error::Error>> {
-    let mut args = vec!["czf"];
-    if !use_gzip {
-        args = vec!["cf"];
-    }
-    
-    args.push(output_path.to_str().unwrap());
-    args.push(".");
-    
-    let result = Command::new("tar")
-        .args(args)
-        .current_dir(source_dir)
-        .output()?;
-    
-    if !result.status.success() {
-        return Err(format!(
-            "Tar compression failed: {}",
-            String::from_utf8_lossy(&result.stderr)
-        ).into());
-    }
-    
-    Ok(())
-}
-
 fn get_extension_for_algorithm(algorithm: &CompressionAlgorithm) -> &'static str {
     match algorithm {
         CompressionAlgorithm::Zip => "zip",
         CompressionAlgorithm::SevenZip => "7z",
         CompressionAlgorithm::Tar => "tar",
         CompressionAlgorithm::TarGz => "tar.gz",
+        CompressionAlgorithm::ZipZstd => "zip",
+        CompressionAlgorithm::TarZst => "tar.zst",
     }
 }
 
@@ -431,40 +627,20 @@ fn get_algorithm_name(algorithm: &CompressionAlgorithm) -> &'static str {
         CompressionAlgorithm::SevenZip => "7-Zip",
         CompressionAlgorithm::Tar => "TAR",
         CompressionAlgorithm::TarGz => "TAR.GZ",
+        CompressionAlgorithm::ZipZstd => "ZIP-ZSTD",
+        CompressionAlgorithm::TarZst => "TAR.ZST",
     }
 }
 
-/// Get available compression algorithms based on system capabilities
+/// Get the available compression algorithms (all of them are written in-process)
 #[command]
 pub fn get_available_compression_algorithms() -> Vec<CompressionAlgorithm> {
-    let mut algorithms = Vec::new();
-    
-    // Zip is available on most systems
-    if cfg!(target_os = "windows") {
-        // PowerShell Compress-Archive is available on Windows 10+
-        algorithms.push(CompressionAlgorithm::Zip);
-    } else {
-        // Check if the zip command is available
-        if Command::new("zip").arg("--help").output().is_ok() {
-            algorithms.push(CompressionAlgorithm::Zip);
-        }
-    }
-    
-    // Check for 7-Zip
-    if Command::new("7z").output().is_ok() {
-        algorithms.push(CompressionAlgorithm::SevenZip);
-    }
-    
-    // Check for tar (usually available on Unix systems)
-    if Command::new("tar").arg("--help").output().is_ok() {
-        algorithms.push(CompressionAlgorithm::Tar);
-        algorithms.push(CompressionAlgorithm::TarGz);
-    }
-    
-    // If no algorithms are available, at least offer zip as fallback
-    if algorithms.is_empty() {
-        algorithms.push(CompressionAlgorithm::Zip);
-    }
-    
-    algorithms
+    vec![
+        CompressionAlgorithm::Zip,
+        CompressionAlgorithm::ZipZstd,
+        CompressionAlgorithm::SevenZip,
+        CompressionAlgorithm::Tar,
+        CompressionAlgorithm::TarGz,
+        CompressionAlgorithm::TarZst,
+    ]
 }
\ No newline at end of file
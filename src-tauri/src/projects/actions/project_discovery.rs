@@ -1,9 +1,12 @@
 use crate::misc::prelude::{log};
 use crate::misc::progress::TaskProgress;
+use crate::misc::tasks::TaskKind;
+use crate::projects::actions::discovery_walker::{self, DiscoveryFilter};
 use crate::projects::models::project::Project;
+use crate::settings::actions::settings_manager;
 use crate::misc::payloads::{ProjectDiscoveryRequest, ProjectDiscoveryResult};
 use std::path::{Path, PathBuf};
-use log::error;
+use log::{error, info};
 use tauri::{command, AppHandle};
 use crate::misc::errors::ErrorLevel;
 
@@ -19,11 +22,15 @@ pub async fn discover_projects(
 ) -> Result<ProjectDiscoveryResult, String> {
     let start_time = std::time::Instant::now();
     let task_id = format!("discover_projects_{}", chrono::Utc::now().timestamp_millis());
-    let progress = TaskProgress::new(
+    let progress = TaskProgress::queued(
         app_handle.clone(),
         task_id,
-        format!("Discovering projects in {}", request.base_folder)
-    );
+        format!("Discovering projects in {}", request.base_folder),
+        TaskKind::Discovery,
+        None,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
 
     match scan_folder_for_projects(
         &app_handle,
@@ -57,6 +64,11 @@ pub async fn discover_projects(
                 scan_duration_ms: duration.as_millis(),
             })
         }
+        Err(e) if progress.is_cancelled() => {
+            // Nothing has been added to the store yet
+            progress.cancelled(Some("Discovery cancelled".to_string()));
+            Err(e.to_string())
+        }
         Err(e) => {
             progress.fail(Some(format!("Discovery failed: {}", e)));
             Err(format!("Failed to discover projects: {}", e))
@@ -72,42 +84,26 @@ pub async fn scan_folder_for_projects(
     ignore_samples: bool,
     progress: &TaskProgress,
 ) -> Result<Vec<Project>, Box<dyn std::error::Error>> {
-    let mut detected_projects = Vec::new();
-
     progress.update(0.1, Some("Scanning for .uproject files...".to_string()));
 
-    // Use glob to find all .uproject files in the specified folder
-    let pattern = format!("{}/**/*.uproject", folder_path);
-    let glob_entries: Vec<_> = glob::glob(&pattern)?.collect();
-    let total_files = glob_entries.len();
-
-    progress.update(0.2, Some(format!("Found {} .uproject files", total_files)));
-
-    'entries: for (index, entry) in glob_entries.into_iter().enumerate() {
-        // Update progress for file scanning
-        let scan_progress = 0.2 + (index as f32 / total_files as f32) * 0.5;
-        progress.update(scan_progress, Some(format!("Scanning file {} of {}", index + 1, total_files)));
-
-        match entry {
-            Ok(path) => {
-                if let Some(project_path) = path.to_str() {
-                    //check if the path pass through a folder named "Templates"
-                    // and if ignore_template is true, skip those projects
-                    for folder in Path::new(project_path).components() {
-                        if (ignore_template && folder.as_os_str() == "Templates")
-                            || (ignore_engine && folder.as_os_str() == "Engine")
-                            || (ignore_samples && folder.as_os_str() == "Samples")
-                        {
-                            continue 'entries;
-                        }
-                    }
-
-                    detected_projects.push(path);
-                }
-            }
-            Err(e) => eprintln!("Error reading glob entry: {}", e),
-        }
-    }
+    // Heavy directories and the exclusions of the settings are not walked at all
+    let settings = settings_manager::load_settings(app_handle)?;
+    let filter = DiscoveryFilter::new(
+        ignore_template,
+        ignore_engine,
+        ignore_samples,
+        &settings.discovery.excluded_patterns,
+    )?;
+    let walk = discovery_walker::find_project_files(
+        Path::new(folder_path),
+        &filter,
+        &progress.sub_task(0.1, 0.6, "Scanning"),
+    )?;
+    info!(
+        "Discovery of {}: {} directories visited, {} skipped, {} unreadable",
+        folder_path, walk.visited_dirs, walk.skipped_dirs, walk.unreadable_dirs
+    );
+    let detected_projects = walk.project_files;
 
     progress.update(0.7, Some("Processing discovered projects...".to_string()));
 
@@ -132,6 +128,8 @@ pub async fn scan_folder_for_projects(
     let total_new = detected_projects.len();
 
     for (index, path) in detected_projects.into_iter().enumerate() {
+        progress.check_cancelled()?;
+
         // Update progress for project processing
         let process_progress = 0.7 + (index as f32 / total_new as f32) * 0.2;
         progress.update(process_progress, Some(format!("Processing project {} of {}", index + 1, total_new)));
@@ -167,10 +165,10 @@ pub fn remove_projects(app_handle: AppHandle, project_paths: Vec<String>) -> Res
         format!("Removing {} project(s)", project_paths.len())
     );
 
-    // Convert the project paths from strings to PathBuf
+    // Convert the project paths (or identifiers) from strings to PathBuf
     let paths_to_remove = project_paths
-        .into_iter()
-        .map(PathBuf::from)
+        .iter()
+        .map(|project| Project::resolve_path(&app_handle, project))
         .collect::<Vec<PathBuf>>();
 
     progress.update(0.5, Some("Updating project store...".to_string()));
@@ -211,7 +209,7 @@ pub fn remove_projects(app_handle: AppHandle, project_paths: Vec<String>) -> Res
 #[command]
 pub fn get_projects(app_handle: AppHandle) -> Result<Vec<Project>, String> {
     // Get the list of projects from the store
-    match Project::get_projects(&app_handle) {
+    match Project::get_projects_with_usage(&app_handle) {
         Ok(projects) => {
             log(
                 &app_handle,
@@ -244,10 +242,10 @@ pub fn rescan_projects(app_handle: AppHandle, project_paths: Vec<String>) -> Res
         format!("Rescanning {} project(s)", project_paths.len())
     );
 
-    // Convert the project paths from strings to PathBuf
+    // Convert the project paths (or identifiers) from strings to PathBuf
     let paths_to_refresh = project_paths
-        .into_iter()
-        .map(PathBuf::from)
+        .iter()
+        .map(|project| Project::resolve_path(&app_handle, project))
         .collect::<Vec<PathBuf>>();
     
     progress.update(0.5, Some("Scanning project metadata...".to_string()));
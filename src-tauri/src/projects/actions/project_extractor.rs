@@ -0,0 +1,351 @@
+use crate::misc::errors::Verror::MessageError;
+use crate::misc::errors::{ErrorLevel, Result};
+use crate::misc::prelude::{format_size, log};
+use crate::misc::progress::TaskProgress;
+use crate::misc::tasks::TaskKind;
+use crate::projects::actions::archive_reader::StagedExtraction;
+use crate::projects::actions::{archive_reader, archive_volumes};
+use crate::projects::models::archive::{ArchiveManifest, ManifestFile};
+use crate::projects::models::project::Project;
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tauri::{command, AppHandle};
+
+/// # Project Extractor Actions
+/// This module brings archived projects back: an archive created by the
+/// compressor (or any supported archive) is extracted to a directory, the
+/// project it contains is detected and can be added to the tracked projects.
+/// Differential backups are restored by replaying their chain, from the full
+/// archive to the requested backup.
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtractionRequest {
+    pub archive_path: String,
+    pub destination_path: String,   // Directory the archive is extracted to (created if missing)
+    #[serde(default)]
+    pub register_project: bool,     // Add the extracted project to the tracked projects
+    #[serde(default)]
+    pub overwrite: bool,            // Replace the files that already exist in the destination
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtractionResult {
+    pub destination_path: String,
+    pub project_path: Option<String>, // .uproject file found in the archive (None if there is none)
+    pub registered: bool,             // True if the project has been added to the tracked projects
+    pub file_count: usize,
+    pub extracted_size: u64,
+    pub duration_ms: u128,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestoreRequest {
+    pub archive_path: String,       // Backup to restore, the last one of its chain
+    pub destination_path: String,   // Directory the project is restored to (created if missing)
+    #[serde(default)]
+    pub register_project: bool,     // Add the restored project to the tracked projects
+    #[serde(default)]
+    pub overwrite: bool,            // Replace the files that already exist in the destination
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestoreResult {
+    pub destination_path: String,
+    pub project_path: Option<String>, // .uproject file of the restored project (None if there is none)
+    pub registered: bool,             // True if the project has been added to the tracked projects
+    pub chain: Vec<String>,           // Archives replayed, from the full archive to the requested backup
+    pub file_count: usize,            // Files of the restored project
+    pub restored_size: u64,
+    pub duration_ms: u128,
+}
+
+/// Extract a project archive to a directory
+#[command]
+pub async fn extract_project_archive(app_handle: AppHandle, request: ExtractionRequest) -> Result<ExtractionResult> {
+    let start_time = std::time::Instant::now();
+    let archive_path = PathBuf::from(&request.archive_path);
+    let destination = PathBuf::from(&request.destination_path);
+
+    // Any volume of a split archive (or its path without the volume number) can be given
+    if archive_volumes::archive_files(&archive_path).is_empty() {
+        let error_msg = format!("Archive does not exist: {}", archive_path.display());
+        error!("{}", error_msg);
+        log(&app_handle, ErrorLevel::Error, &error_msg);
+        return Err(MessageError(error_msg));
+    }
+
+    let archive_name = archive_path
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("archive");
+
+    let task_id = format!("extract_archive_{}", chrono::Utc::now().timestamp_millis());
+    let progress = TaskProgress::queued(
+        app_handle.clone(),
+        task_id,
+        format!("Extracting archive: {}", archive_name),
+        TaskKind::Extract,
+        None,
+    )
+    .await?;
+
+    info!("Extracting {} to {}", archive_path.display(), destination.display());
+    log(
+        &app_handle,
+        ErrorLevel::Info,
+        &format!("Extracting {} to {}", archive_name, destination.display()),
+    );
+
+    let summary = match archive_reader::extract_archive(&archive_path, &destination, request.overwrite, &progress) {
+        Ok(summary) => summary,
+        Err(_) if progress.is_cancelled() => {
+            let cancel_msg = format!("Extraction of {} cancelled", archive_name);
+            log(&app_handle, ErrorLevel::Warning, &cancel_msg);
+            progress.cancelled(Some(cancel_msg));
+            return Err(progress.cancellation_error());
+        }
+        Err(e) => {
+            let error_msg = format!("Failed to extract {}: {}", archive_name, e);
+            log(&app_handle, ErrorLevel::Error, &error_msg);
+            progress.fail(Some(error_msg.clone()));
+            return Err(MessageError(error_msg));
+        }
+    };
+
+    let project_path = find_project_file(&summary.files).map(|path| destination.join(path));
+    let registered = request.register_project && register_project(&app_handle, project_path.as_deref(), archive_name);
+
+    let completion_msg = format!(
+        "Extracted {} files ({}) from {}",
+        summary.files.len(),
+        format_size(summary.size),
+        archive_name
+    );
+    log(&app_handle, ErrorLevel::Info, &completion_msg);
+    progress.complete(Some(completion_msg));
+
+    Ok(ExtractionResult {
+        destination_path: destination.display().to_string(),
+        project_path: project_path.map(|path| path.display().to_string()),
+        registered,
+        file_count: summary.files.len(),
+        extracted_size: summary.size,
+        duration_ms: start_time.elapsed().as_millis(),
+    })
+}
+
+/// Restore a differential backup (or a full archive) by replaying its chain of archives
+#[command]
+pub async fn restore_project_backup(app_handle: AppHandle, request: RestoreRequest) -> Result<RestoreResult> {
+    let start_time = std::time::Instant::now();
+    let archive_path = PathBuf::from(&request.archive_path);
+    let destination = PathBuf::from(&request.destination_path);
+
+    if archive_volumes::archive_files(&archive_path).is_empty() {
+        let error_msg = format!("Archive does not exist: {}", archive_path.display());
+        error!("{}", error_msg);
+        log(&app_handle, ErrorLevel::Error, &error_msg);
+        return Err(MessageError(error_msg));
+    }
+
+    let archive_name = archive_path
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("archive");
+
+    let task_id = format!("restore_backup_{}", chrono::Utc::now().timestamp_millis());
+    let progress = TaskProgress::queued(
+        app_handle.clone(),
+        task_id,
+        format!("Restoring backup: {}", archive_name),
+        TaskKind::Extract,
+        None,
+    )
+    .await?;
+
+    info!("Restoring {} to {}", archive_path.display(), destination.display());
+
+    let (chain, snapshot) = match restore_chain(&app_handle, &archive_path, &destination, request.overwrite, &progress) {
+        Ok(restored) => restored,
+        Err(_) if progress.is_cancelled() => {
+            let cancel_msg = format!("Restoration of {} cancelled", archive_name);
+            log(&app_handle, ErrorLevel::Warning, &cancel_msg);
+            progress.cancelled(Some(cancel_msg));
+            return Err(progress.cancellation_error());
+        }
+        Err(e) => {
+            let error_msg = format!("Failed to restore {}: {}", archive_name, e);
+            log(&app_handle, ErrorLevel::Error, &error_msg);
+            progress.fail(Some(error_msg.clone()));
+            return Err(MessageError(error_msg));
+        }
+    };
+
+    let files: Vec<PathBuf> = snapshot
+        .iter()
+        .filter_map(|file| archive_reader::entry_path(&file.name))
+        .collect();
+    let restored_size = snapshot.iter().map(|file| file.size).sum();
+    let project_path = find_project_file(&files).map(|path| destination.join(path));
+    let registered = request.register_project && register_project(&app_handle, project_path.as_deref(), archive_name);
+
+    let completion_msg = format!(
+        "Restored {} files ({}) from {} archive(s) of {}",
+        files.len(),
+        format_size(restored_size),
+        chain.len(),
+        archive_name
+    );
+    log(&app_handle, ErrorLevel::Info, &completion_msg);
+    progress.complete(Some(completion_msg));
+
+    Ok(RestoreResult {
+        destination_path: destination.display().to_string(),
+        project_path: project_path.map(|path| path.display().to_string()),
+        registered,
+        chain: chain.iter().map(|path| path.display().to_string()).collect(),
+        file_count: files.len(),
+        restored_size,
+        duration_ms: start_time.elapsed().as_millis(),
+    })
+}
+
+/// Replay the chain of a backup to a directory. Returns the archives replayed and the restored files.
+/// The chain is replayed in a staging directory, the destination is only changed once the whole
+/// snapshot has been restored.
+fn restore_chain(
+    app_handle: &AppHandle,
+    archive_path: &Path,
+    destination: &Path,
+    overwrite: bool,
+    progress: &TaskProgress,
+) -> Result<(Vec<PathBuf>, Vec<ManifestFile>)> {
+    let chain = resolve_backup_chain(archive_path, &progress.sub_task(0.0, 0.1, "Reading the backup chain"))?;
+    let weight = 0.9 / chain.len() as f32;
+    let mut extraction = StagedExtraction::new(destination, overwrite)?;
+
+    for (index, (path, manifest)) in chain.iter().enumerate() {
+        let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        log(app_handle, ErrorLevel::Info, &format!("Restoring {} ({}/{})", name, index + 1, chain.len()));
+
+        // The differential backups replace the files changed since their parent
+        let archive_progress = progress.sub_task(0.1 + index as f32 * weight, weight, &name);
+        extraction.extract(path, &archive_progress)?;
+
+        for deleted in &manifest.deleted {
+            if let Some(relative_path) = archive_reader::entry_path(deleted) {
+                extraction
+                    .remove_file(&relative_path)
+                    .map_err(|e| MessageError(format!("Failed to delete {}: {}", deleted, e)))?;
+            }
+        }
+    }
+
+    // The restored directory must hold every file of the snapshot of the requested backup
+    let snapshot = chain.last().map(|(_, manifest)| manifest.snapshot()).unwrap_or_default();
+    if let Some(missing) = snapshot.iter().find(|file| {
+        archive_reader::entry_path(&file.name)
+            .map(|path| !extraction.staging_dir().join(path).is_file())
+            .unwrap_or(true)
+    }) {
+        return Err(MessageError(format!("{} is missing from the backup chain", missing.name)));
+    }
+    extraction.commit()?;
+
+    Ok((chain.into_iter().map(|(path, _)| path).collect(), snapshot))
+}
+
+/// Find the archives of the chain of a backup, from the full archive to the backup itself.
+/// Each link is checked: the unchanged files of a backup must be in the snapshot of its parent.
+fn resolve_backup_chain(archive_path: &Path, progress: &TaskProgress) -> Result<Vec<(PathBuf, ArchiveManifest)>> {
+    let mut chain: Vec<(PathBuf, ArchiveManifest)> = Vec::new();
+    let mut path = archive_path.to_path_buf();
+
+    loop {
+        progress.check_cancelled()?;
+        let manifest = archive_reader::read_manifest(&path, progress)?;
+
+        if let Some((child_path, child)) = chain.last() {
+            let snapshot: HashMap<&str, &str> = manifest
+                .files
+                .iter()
+                .chain(&manifest.unchanged)
+                .map(|file| (file.name.as_str(), file.sha256.as_str()))
+                .collect();
+            if child
+                .unchanged
+                .iter()
+                .any(|file| snapshot.get(file.name.as_str()) != Some(&file.sha256.as_str()))
+            {
+                return Err(MessageError(format!(
+                    "{} does not match its previous backup {}",
+                    child_path.display(),
+                    path.display()
+                )));
+            }
+        }
+
+        let parent = manifest.parent.clone();
+        chain.push((archive_volumes::volume_base(&path).unwrap_or(path), manifest));
+
+        let Some(parent) = parent else {
+            break;
+        };
+        // The archives of a chain are written in the same directory
+        path = archive_path.with_file_name(&parent);
+        if archive_volumes::archive_files(&path).is_empty() {
+            return Err(MessageError(format!(
+                "The previous backup {} of the chain is missing",
+                path.display()
+            )));
+        }
+        if chain.iter().any(|(chained_path, _)| *chained_path == path) {
+            return Err(MessageError(format!("The backup chain of {} loops", archive_path.display())));
+        }
+    }
+
+    chain.reverse();
+    Ok(chain)
+}
+
+/// Add an extracted project to the tracked projects, returns true on success
+fn register_project(app_handle: &AppHandle, project_path: Option<&Path>, archive_name: &str) -> bool {
+    let Some(project_path) = project_path else {
+        log(
+            app_handle,
+            ErrorLevel::Warning,
+            &format!("No project file found in {}, nothing to register", archive_name),
+        );
+        return false;
+    };
+
+    match Project::try_from_path(&project_path.to_path_buf())
+        .and_then(|project| Project::add_projects(app_handle, &[project]))
+    {
+        Ok(_) => true,
+        Err(e) => {
+            error!("Failed to register the extracted project: {}", e);
+            log(
+                app_handle,
+                ErrorLevel::Error,
+                &format!("Failed to register the extracted project: {}", e),
+            );
+            false
+        }
+    }
+}
+
+/// Find the project file among the extracted files, the closest to the root wins
+fn find_project_file(files: &[PathBuf]) -> Option<&Path> {
+    files
+        .iter()
+        .filter(|path| {
+            path.extension()
+                .map(|extension| extension.eq_ignore_ascii_case("uproject"))
+                .unwrap_or(false)
+        })
+        .min_by_key(|path| path.components().count())
+        .map(PathBuf::as_path)
+}
@@ -0,0 +1,269 @@
+use crate::misc::errors::Verror::MessageError;
+use crate::misc::errors::{ErrorLevel, Result};
+use crate::misc::prelude::log;
+use crate::misc::progress::TaskProgress;
+use crate::misc::tasks::{TaskKind, TaskRecord};
+use crate::projects::actions::backup_scheduler;
+use crate::projects::actions::discovery_walker;
+use crate::projects::actions::library_watcher::{self, LibraryWatcher};
+use crate::projects::models::project::Project;
+use crate::projects::models::session::ProjectSession;
+use crate::settings::actions::{launch_profile_manager, settings_manager};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tauri::{command, AppHandle, Manager};
+
+/// # Project Health
+/// This module finds the tracked projects whose project file does not exist anymore.
+/// They are flagged as missing, and searched in the library folders: a project file
+/// with the same name is a candidate, recognized by the hashes of its files (see
+/// `Project::file_hashes`). A project is relinked to a candidate matching all its hashes,
+/// the other candidates are reported to be relinked by the user (see `relink_project`).
+/// The data of the projects (launch profiles, backup schedule, sessions and task history)
+/// is keyed by their identifier, a relinked project keeps it.
+
+/// A project found missing by a health check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectHealth {
+    pub project_path: String,                  // Path of the project file before it went missing
+    pub relinked_to: Option<String>,           // New path of the project, if it has been relinked automatically
+    pub candidates: Vec<RelocationCandidate>, // Project files that may be the project, best first
+}
+
+/// A project file that may be a missing project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelocationCandidate {
+    pub path: String,          // Project file
+    pub matching_files: usize, // Files with the same hash as the missing project
+    pub hashed_files: usize,   // Files hashed when the missing project was scanned
+}
+
+/// Check the projects in the background on startup
+pub fn start_health_check(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = check_projects_health(app_handle).await {
+            error!("Failed to check the health of the projects: {}", e);
+        }
+    });
+}
+
+/// Flag the missing projects and try to find them in the library folders
+#[command]
+pub async fn check_projects_health(app_handle: AppHandle) -> Result<Vec<ProjectHealth>> {
+    let task_id = format!("check_projects_health_{}", chrono::Utc::now().timestamp_millis());
+    let progress = TaskProgress::queued(
+        app_handle.clone(),
+        task_id,
+        "Checking the projects".to_string(),
+        TaskKind::Discovery,
+        None,
+    )
+    .await?;
+
+    match check_projects(&app_handle, &progress) {
+        Ok(report) => {
+            let relinked = report.iter().filter(|health| health.relinked_to.is_some()).count();
+            let message = format!("{} missing project(s), {} relinked", report.len(), relinked);
+            if !report.is_empty() {
+                log(&app_handle, ErrorLevel::Warning, &message);
+            }
+            progress.complete(Some(message));
+            Ok(report)
+        }
+        Err(_) if progress.is_cancelled() => {
+            progress.cancelled(None);
+            Err(progress.cancellation_error())
+        }
+        Err(e) => {
+            log(
+                &app_handle,
+                ErrorLevel::Error,
+                &format!("Failed to check the projects: {}", e),
+            );
+            progress.fail(Some(e.to_string()));
+            Err(e)
+        }
+    }
+}
+
+/// Move a project and its data to a new project file
+#[command]
+pub fn relink_project(app_handle: AppHandle, old_path: String, new_path: String) -> Result<Project> {
+    let old_path = Project::resolve_path(&app_handle, &old_path).display().to_string();
+    let project = relink(&app_handle, Path::new(&old_path), Path::new(&new_path))?;
+    log(
+        &app_handle,
+        ErrorLevel::Info,
+        &format!("Relinked the project {} to {}", old_path, new_path),
+    );
+
+    Ok(project)
+}
+
+fn check_projects(app_handle: &AppHandle, progress: &TaskProgress) -> Result<Vec<ProjectHealth>> {
+    let projects = {
+        let library = app_handle.state::<LibraryWatcher>();
+        let _updates_guard = library.updates.lock().unwrap();
+        let mut projects = Project::get_projects(app_handle).map_err(|e| MessageError(e.to_string()))?;
+
+        // The projects scanned before the hashes existed are hashed while they are there
+        let mut changed = false;
+        for project in projects.iter_mut() {
+            let missing = !project.path.is_file();
+            if missing != project.missing {
+                project.missing = missing;
+                changed = true;
+            }
+            if !missing && project.file_hashes.is_empty() {
+                project.file_hashes = Project::hash_identity_files(&project.path);
+                changed = true;
+            }
+        }
+        if changed {
+            Project::save_projects(app_handle, &projects).map_err(|e| MessageError(e.to_string()))?;
+        }
+        projects
+    };
+
+    let missing: Vec<&Project> = projects.iter().filter(|project| project.missing).collect();
+    if missing.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Project files of the library folders
+    let settings = settings_manager::load_settings(app_handle)?;
+    let filter = library_watcher::library_filter(app_handle)?;
+    let roots = &settings.discovery.library_roots;
+    let mut project_files = Vec::new();
+    for (index, root) in roots.iter().enumerate() {
+        let weight = 0.9 / roots.len() as f32;
+        let root_progress = progress.sub_task(index as f32 * weight, weight, root);
+        match discovery_walker::find_project_files(Path::new(root), &filter, &root_progress) {
+            Ok(walk) => project_files.extend(walk.project_files),
+            Err(_) if progress.is_cancelled() => return Err(progress.cancellation_error()),
+            Err(e) => warn!("Library {} not searched for missing projects: {}", root, e),
+        }
+    }
+
+    let mut report = Vec::new();
+    let mut claimed: HashSet<PathBuf> = HashSet::new();
+    for project in missing {
+        let candidates = find_candidates(project, &project_files);
+
+        // Only an unambiguous and identical project is relinked without asking
+        let mut relinked_to = None;
+        if let [candidate] = &candidates[..] {
+            let path = PathBuf::from(&candidate.path);
+            if candidate.matching_files == candidate.hashed_files && claimed.insert(path.clone()) {
+                match relink(app_handle, &project.path, &path) {
+                    Ok(_) => {
+                        info!("Relinked the missing project {} to {}", project.path.display(), path.display());
+                        relinked_to = Some(candidate.path.clone());
+                    }
+                    Err(e) => warn!("Failed to relink {}: {}", project.path.display(), e),
+                }
+            }
+        }
+
+        report.push(ProjectHealth {
+            project_path: project.path.display().to_string(),
+            relinked_to,
+            candidates,
+        });
+    }
+
+    Ok(report)
+}
+
+/// Project files with the name of a missing project and at least one of its hashes, best first
+pub(crate) fn find_candidates(project: &Project, project_files: &[PathBuf]) -> Vec<RelocationCandidate> {
+    let Some(file_name) = project.path.file_name() else {
+        return Vec::new();
+    };
+
+    let mut candidates: Vec<RelocationCandidate> = project_files
+        .iter()
+        .filter(|path| {
+            path.file_name()
+                .map(|name| name.eq_ignore_ascii_case(file_name))
+                .unwrap_or(false)
+        })
+        .filter_map(|path| {
+            let hashes = Project::hash_identity_files(path);
+            let matching_files = project
+                .file_hashes
+                .iter()
+                .filter(|(file, hash)| hashes.get(*file) == Some(hash))
+                .count();
+
+            (matching_files > 0).then(|| RelocationCandidate {
+                path: path.display().to_string(),
+                matching_files,
+                hashed_files: project.file_hashes.len(),
+            })
+        })
+        .collect();
+    candidates.sort_by_key(|candidate| std::cmp::Reverse(candidate.matching_files));
+
+    candidates
+}
+
+/// Replace a project by the project file at a new path. Its data is keyed by its identifier,
+/// which it keeps, so it follows the project. A project already tracked at the new path is
+/// replaced, its data is merged into the relinked project.
+fn relink(app_handle: &AppHandle, old_path: &Path, new_path: &Path) -> Result<Project> {
+    if old_path == new_path {
+        return Err(MessageError("The project is already at this path".to_string()));
+    }
+    if !discovery_walker::is_project_file(new_path) || !new_path.is_file() {
+        return Err(MessageError(format!("{} is not a project file", new_path.display())));
+    }
+
+    // The library watcher must not save the projects between their reading and their saving here
+    let library = app_handle.state::<LibraryWatcher>();
+    let _updates_guard = library.updates.lock().unwrap();
+    let mut projects = Project::get_projects(app_handle).map_err(|e| MessageError(e.to_string()))?;
+    let Some(id) = projects.iter().find(|project| project.path == old_path).map(|project| project.id.clone()) else {
+        return Err(MessageError(format!("{} is not a tracked project", old_path.display())));
+    };
+    let scanned = Project::try_from_path(&new_path.to_path_buf())
+        .map_err(|e| MessageError(format!("Failed to scan {}: {}", new_path.display(), e)))?;
+
+    // The data is merged before the projects are saved: if a save fails, both projects are still tracked
+    if let Some(replaced) = projects.iter().find(|project| project.path == new_path && project.id != id) {
+        merge_project_data(app_handle, &replaced.id, &id)?;
+    }
+
+    let relinked = Project { id, ..scanned };
+    projects.retain(|project| project.path != new_path);
+    if let Some(project) = projects.iter_mut().find(|project| project.path == old_path) {
+        *project = relinked.clone();
+    }
+    Project::save_projects(app_handle, &projects).map_err(|e| MessageError(e.to_string()))?;
+
+    Ok(relinked)
+}
+
+/// Move the data of a project replaced by another one: the launch profiles and backup schedule of the
+/// remaining project win over the ones with the same name, the backup states, sessions and task history are merged.
+fn merge_project_data(app_handle: &AppHandle, from_id: &str, into_id: &str) -> Result<()> {
+    let mut settings = settings_manager::load_settings(app_handle)?;
+    if let Some(mut profiles) = settings.launch_profiles.project_profiles.remove(from_id) {
+        if let Some(kept_profiles) = settings.launch_profiles.project_profiles.remove(into_id) {
+            launch_profile_manager::merge_profiles(&mut profiles, kept_profiles);
+        }
+        settings.launch_profiles.project_profiles.insert(into_id.to_string(), profiles);
+    }
+    if let Some(schedule) = settings.backups.project_schedules.remove(from_id) {
+        settings.backups.project_schedules.entry(into_id.to_string()).or_insert(schedule);
+    }
+    settings_manager::store_settings(app_handle, &settings)?;
+
+    backup_scheduler::move_backup_state(app_handle, from_id, into_id)?;
+    ProjectSession::move_to_project(app_handle, from_id, into_id).map_err(|e| MessageError(e.to_string()))?;
+    TaskRecord::move_to_project(app_handle, from_id, into_id)?;
+
+    Ok(())
+}
@@ -1,43 +1,136 @@
+use crate::engines::actions::engine_registry;
+use crate::engines::models::engine::{EngineInstall, EngineVersion};
 use crate::misc::errors::ErrorLevel;
 use crate::misc::prelude::log;
+use crate::projects::actions::process_supervisor::ProcessSupervisor;
+use crate::projects::models::process::ProcessKind;
+use crate::projects::models::project::Project;
+use crate::settings::actions::launch_profile_manager;
+use crate::settings::models::settings::LaunchMode;
 use log::{info};
 use std::path::{Path, PathBuf};
 use std::process::Command;
-use tauri::{command, AppHandle};
+use tauri::{command, AppHandle, State};
 
-/// Launch a project with Unreal Engine
+/// Launch a project with the editor of the engine it is associated with
 #[command]
 pub async fn launch_project_with_engine(
     app_handle: AppHandle,
+    supervisor: State<'_, ProcessSupervisor>,
     project_path: String,
 ) -> Result<(), String> {
+    let project_path = Project::resolve_path(&app_handle, &project_path);
+
+    if !project_path.exists() {
+        let error_msg = format!(
+            "Project file does not exist: {} (if the project has been moved, relink it to its new location)",
+            project_path.display()
+        );
+        log(&app_handle, ErrorLevel::Error, &error_msg);
+        return Err(error_msg);
+    }
+
+    let (editor_path, engine) = resolve_project_editor(&app_handle, &project_path)?;
+
+    log(
+        &app_handle,
+        ErrorLevel::Info,
+        &format!(
+            "Launching project: {} with {}",
+            project_path.display(),
+            editor_path.display()
+        ),
+    );
+
+    let result = supervisor.spawn(
+        &app_handle,
+        Command::new(&editor_path).arg(&project_path),
+        ProcessKind::Editor,
+        Some(&project_path),
+        None,
+        engine.version.as_ref().map(EngineVersion::full_version),
+    );
+
+    match result {
+        Ok(_) => {
+            log(
+                &app_handle,
+                ErrorLevel::Info,
+                "Project launched successfully",
+            );
+            Ok(())
+        }
+        Err(e) => {
+            let error_msg = format!("Failed to launch project: {}", e);
+            log(&app_handle, ErrorLevel::Error, &error_msg);
+            Err(error_msg)
+        }
+    }
+}
+
+/// Launch a project with one of its launch profiles
+#[command]
+pub async fn launch_project_with_profile(
+    app_handle: AppHandle,
+    supervisor: State<'_, ProcessSupervisor>,
+    project_path: String,
+    profile_name: String,
+) -> Result<(), String> {
+    let project_path = Project::resolve_path(&app_handle, &project_path).display().to_string();
+    let profile = launch_profile_manager::find_launch_profile(&app_handle, &project_path, &profile_name)
+        .map_err(|e| {
+            log(&app_handle, ErrorLevel::Error, &e.to_string());
+            e.to_string()
+        })?;
     let project_path = PathBuf::from(project_path);
 
     if !project_path.exists() {
-        let error_msg = format!("Project file does not exist: {}", project_path.display());
+        let error_msg = format!(
+            "Project file does not exist: {} (if the project has been moved, relink it to its new location)",
+            project_path.display()
+        );
         log(&app_handle, ErrorLevel::Error, &error_msg);
         return Err(error_msg);
     }
 
+    let (editor_path, engine) = resolve_project_editor(&app_handle, &project_path)?;
+    let working_directory = profile
+        .working_directory
+        .clone()
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| project_path.parent().unwrap().to_path_buf());
+    let arguments = profile.arguments(&project_path);
+
     log(
         &app_handle,
         ErrorLevel::Info,
-        &format!("Launching project: {}", project_path.display()),
+        &format!(
+            "Launching project with profile \"{}\": {} {}",
+            profile.name,
+            editor_path.display(),
+            arguments.join(" ")
+        ),
     );
 
-    let result = if cfg!(target_os = "windows") {
-        // On Windows, we can directly open the .uproject file
-        Command::new("cmd")
-            .args(["/C", "start", "", project_path.to_str().unwrap()])
-            .spawn()
-    } else if cfg!(target_os = "macos") {
-        // On macOS, use the open command
-        Command::new("open").arg(project_path).spawn()
-    } else {
-        // On Linux, try to open with the default application
-        Command::new("xdg-open").arg(project_path).spawn()
+    let kind = match profile.mode {
+        LaunchMode::Editor => ProcessKind::Editor,
+        LaunchMode::Game => ProcessKind::Game,
+        LaunchMode::Server => ProcessKind::Server,
     };
 
+    let result = supervisor.spawn(
+        &app_handle,
+        Command::new(&editor_path)
+            .args(&arguments)
+            .envs(&profile.environment)
+            .current_dir(&working_directory),
+        kind,
+        Some(&project_path),
+        None,
+        engine.version.as_ref().map(EngineVersion::full_version),
+    );
+
     match result {
         Ok(_) => {
             log(
@@ -55,18 +148,56 @@ pub async fn launch_project_with_engine(
     }
 }
 
+/// Resolve the engine a project is associated with and its editor executable
+pub fn resolve_project_editor(
+    app_handle: &AppHandle,
+    project_path: &Path,
+) -> Result<(PathBuf, EngineInstall), String> {
+    let engine = match engine_registry::read_engine_association(project_path)
+        .and_then(|association| engine_registry::resolve_engine(app_handle, &association, project_path))
+    {
+        Ok(engine) => engine,
+        Err(e) => {
+            let error_msg = format!(
+                "Failed to resolve the engine of {}: {}",
+                project_path.display(),
+                e
+            );
+            log(app_handle, ErrorLevel::Error, &error_msg);
+            return Err(error_msg);
+        }
+    };
+
+    let editor_path = engine.editor_binary();
+    if !editor_path.exists() {
+        let error_msg = format!(
+            "Editor executable does not exist: {} (is the engine {} built?)",
+            editor_path.display(),
+            engine.name
+        );
+        log(app_handle, ErrorLevel::Error, &error_msg);
+        return Err(error_msg);
+    }
+
+    Ok((editor_path, engine))
+}
+
 /// Launch a project with IDE
 #[command]
 pub async fn launch_project_with_ide(
     app_handle: AppHandle,
+    supervisor: State<'_, ProcessSupervisor>,
     project_path: String,
     ide_path: String,
 ) -> Result<(), String> {
-    let project_path = PathBuf::from(project_path);
+    let project_path = Project::resolve_path(&app_handle, &project_path);
     let ide_path = PathBuf::from(ide_path);
 
     if !project_path.exists() {
-        let error_msg = format!("Project file does not exist: {}", project_path.display());
+        let error_msg = format!(
+            "Project file does not exist: {} (if the project has been moved, relink it to its new location)",
+            project_path.display()
+        );
         log(&app_handle, ErrorLevel::Error, &error_msg);
         return Err(error_msg);
     }
@@ -95,7 +226,14 @@ pub async fn launch_project_with_ide(
         &format!("Launching project with IDE: {}", file_to_open.display()),
     );
 
-    let result = Command::new(&ide_path).arg(&file_to_open).spawn();
+    let result = supervisor.spawn(
+        &app_handle,
+        Command::new(&ide_path).arg(&file_to_open),
+        ProcessKind::Ide,
+        Some(&project_path),
+        None,
+        None,
+    );
 
     match result {
         Ok(_) => {
@@ -118,6 +256,7 @@ pub async fn launch_project_with_ide(
 #[command]
 pub async fn launch_custom_engine_with_ide(
     app_handle: AppHandle,
+    supervisor: State<'_, ProcessSupervisor>,
     custom_engine_dir: String,
     ide_path: String,
 ) -> Result<(), String> {
@@ -163,7 +302,14 @@ pub async fn launch_custom_engine_with_ide(
         ),
     );
 
-    let result = Command::new(&ide_path).arg(&file_to_open).spawn();
+    let result = supervisor.spawn(
+        &app_handle,
+        Command::new(&ide_path).arg(&file_to_open),
+        ProcessKind::Ide,
+        None,
+        None,
+        None,
+    );
 
     match result {
         Ok(_) => {
@@ -182,6 +328,74 @@ pub async fn launch_custom_engine_with_ide(
     }
 }
 
+/// Open a source file in an IDE, at a given line when the IDE supports it (e.g. from a build diagnostic)
+#[command]
+pub async fn open_file_in_ide(
+    app_handle: AppHandle,
+    ide_path: String,
+    file_path: String,
+    line: Option<u32>,
+    column: Option<u32>,
+) -> Result<(), String> {
+    let ide_path = PathBuf::from(ide_path);
+
+    if !Path::new(&file_path).exists() {
+        let error_msg = format!("File does not exist: {}", file_path);
+        log(&app_handle, ErrorLevel::Error, &error_msg);
+        return Err(error_msg);
+    }
+
+    if !ide_path.exists() {
+        let error_msg = format!("IDE executable does not exist: {}", ide_path.display());
+        log(&app_handle, ErrorLevel::Error, &error_msg);
+        return Err(error_msg);
+    }
+
+    let arguments = ide_goto_arguments(&ide_path, &file_path, line, column);
+    info!("Opening file in IDE: {} {}", ide_path.display(), arguments.join(" "));
+
+    match Command::new(&ide_path).args(&arguments).spawn() {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            let error_msg = format!("Failed to open {} in IDE: {}", file_path, e);
+            log(&app_handle, ErrorLevel::Error, &error_msg);
+            Err(error_msg)
+        }
+    }
+}
+
+/// Build the arguments opening a file at a line, based on the IDE executable name
+fn ide_goto_arguments(ide_path: &Path, file_path: &str, line: Option<u32>, column: Option<u32>) -> Vec<String> {
+    let ide_name = ide_path
+        .file_stem()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let Some(line) = line else {
+        return vec![file_path.to_string()];
+    };
+    let column = column.unwrap_or(1);
+
+    if ide_name.contains("code") || ide_name.contains("codium") || ide_name.contains("cursor") {
+        // Visual Studio Code and its forks
+        vec!["--goto".to_string(), format!("{}:{}:{}", file_path, line, column)]
+    } else if ["rider", "clion", "idea"].iter().any(|jetbrains| ide_name.contains(jetbrains)) {
+        vec![
+            "--line".to_string(),
+            line.to_string(),
+            "--column".to_string(),
+            column.to_string(),
+            file_path.to_string(),
+        ]
+    } else if ide_name.contains("subl") {
+        vec![format!("{}:{}:{}", file_path, line, column)]
+    } else if ide_name == "devenv" {
+        // Visual Studio cannot go to a line from the command line
+        vec!["/Edit".to_string(), file_path.to_string()]
+    } else {
+        vec![file_path.to_string()]
+    }
+}
+
 /// Find .sln file in the project directory
 fn find_sln_file(project_dir: &Path) -> Option<PathBuf> {
     if let Ok(entries) = std::fs::read_dir(project_dir) {
@@ -197,8 +411,8 @@ fn find_sln_file(project_dir: &Path) -> Option<PathBuf> {
 
 /// Check if a project has C++ code by looking for the Source directory
 #[command]
-pub fn project_has_cpp(project_path: String) -> Result<bool, String> {
-    let project_path = PathBuf::from(project_path);
+pub fn project_has_cpp(app_handle: AppHandle, project_path: String) -> Result<bool, String> {
+    let project_path = Project::resolve_path(&app_handle, &project_path);
 
     if let Some(project_dir) = project_path.parent() {
         let source_dir = project_dir.join("Source");
@@ -0,0 +1,160 @@
+use crate::misc::errors::Verror::ProjectLockedError;
+use crate::misc::errors::{ErrorLevel, Result};
+use crate::misc::prelude::log;
+use crate::projects::actions::process_supervisor::ProcessSupervisor;
+use crate::projects::models::project::Project;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+use tauri::{command, AppHandle, Manager};
+
+/// # Project Lock Detection
+/// This module detects if a project is currently open, to prevent destructive
+/// operations (cleaning, compressing, moving...) while an editor uses its files.
+/// A project is considered open when:
+/// - a process launched by the app is running for it,
+/// - a running editor process references its .uproject in its command line,
+/// - lock files exist in its Saved directory.
+
+/// Names of the editor executables (lowercase, without extension)
+const EDITOR_PROCESS_NAMES: [&str; 2] = ["unrealeditor", "ue4editor"];
+
+/// Reason why a project is considered open
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ProjectLock {
+    TrackedProcess(u32),                      // Process launched by the app (pid)
+    EditorProcess { pid: u32, name: String }, // Editor process started outside the app
+    LockFile(PathBuf),                        // Lock file found under Saved/
+}
+
+impl std::fmt::Display for ProjectLock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProjectLock::TrackedProcess(pid) => write!(f, "process {} launched by the manager", pid),
+            ProjectLock::EditorProcess { pid, name } => write!(f, "{} running with pid {}", name, pid),
+            ProjectLock::LockFile(path) => write!(f, "lock file {}", path.display()),
+        }
+    }
+}
+
+/// Get the reasons why a project is considered open (empty if it is not)
+#[command]
+pub fn get_project_locks(app_handle: AppHandle, project_path: String) -> Result<Vec<ProjectLock>> {
+    let project_path = Project::resolve_path(&app_handle, &project_path);
+    Ok(detect_project_locks(&app_handle, &project_path))
+}
+
+/// Fail if the project is open, unless the operation is forced
+pub fn ensure_project_unlocked(app_handle: &AppHandle, project_path: &Path, force: bool) -> Result<()> {
+    let locks = detect_project_locks(app_handle, project_path);
+    if locks.is_empty() {
+        return Ok(());
+    }
+
+    let reasons = locks
+        .iter()
+        .map(ProjectLock::to_string)
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    if force {
+        let warning = format!(
+            "Forcing operation on open project {} ({})",
+            project_path.display(),
+            reasons
+        );
+        warn!("{}", warning);
+        log(app_handle, ErrorLevel::Warning, &warning);
+        return Ok(());
+    }
+
+    let error = ProjectLockedError(project_path.display().to_string(), reasons);
+    log(app_handle, ErrorLevel::Error, &error.to_string());
+    Err(error)
+}
+
+/// Detect every reason why a project is considered open
+pub fn detect_project_locks(app_handle: &AppHandle, project_path: &Path) -> Vec<ProjectLock> {
+    let mut locks = Vec::new();
+
+    // Step 1: Processes launched by the app
+    let tracked_pids: Vec<u32> = match app_handle.try_state::<ProcessSupervisor>() {
+        Some(supervisor) => supervisor
+            .running()
+            .into_iter()
+            .filter(|process| {
+                process.project_path.as_deref().map(Path::new) == Some(project_path)
+            })
+            .map(|process| process.pid)
+            .collect(),
+        None => Vec::new(),
+    };
+    locks.extend(tracked_pids.iter().map(|pid| ProjectLock::TrackedProcess(*pid)));
+
+    // Step 2: Editor processes started outside the app
+    locks.extend(
+        find_editor_processes(project_path)
+            .into_iter()
+            .filter(|(pid, _)| !tracked_pids.contains(pid))
+            .map(|(pid, name)| ProjectLock::EditorProcess { pid, name }),
+    );
+
+    // Step 3: Lock files left in the Saved directory
+    if let Some(project_dir) = project_path.parent() {
+        let saved_dir = project_dir.join("Saved");
+        for pattern in ["*.lock", "*/*.lock"] {
+            let pattern = format!("{}/{}", saved_dir.display(), pattern);
+            if let Ok(entries) = glob::glob(&pattern) {
+                locks.extend(entries.flatten().map(ProjectLock::LockFile));
+            }
+        }
+    }
+
+    if !locks.is_empty() {
+        info!(
+            "Project {} is open: {}",
+            project_path.display(),
+            locks.iter().map(ProjectLock::to_string).collect::<Vec<String>>().join(", ")
+        );
+    }
+
+    locks
+}
+
+/// Find the editor processes whose command line references the project, returns their pid and name
+fn find_editor_processes(project_path: &Path) -> Vec<(u32, String)> {
+    let Ok(project_path) = project_path.canonicalize() else {
+        return Vec::new();
+    };
+
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::nothing()
+            .with_cmd(UpdateKind::Always)
+            .with_cwd(UpdateKind::Always),
+    );
+
+    system
+        .processes()
+        .iter()
+        .filter(|(_, process)| {
+            let name = process.name().to_string_lossy().to_lowercase();
+            EDITOR_PROCESS_NAMES.iter().any(|editor| name.starts_with(editor))
+        })
+        .filter(|(_, process)| {
+            process.cmd().iter().any(|argument| {
+                let argument = Path::new(argument);
+                // Relative paths are relative to the working directory of the editor
+                let argument = match process.cwd() {
+                    Some(cwd) if argument.is_relative() => cwd.join(argument),
+                    _ => argument.to_path_buf(),
+                };
+                argument.canonicalize().map(|p| p == project_path).unwrap_or(false)
+            })
+        })
+        .map(|(pid, process)| (pid.as_u32(), process.name().to_string_lossy().to_string()))
+        .collect()
+}
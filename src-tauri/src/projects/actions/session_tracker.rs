@@ -0,0 +1,40 @@
+use crate::misc::errors::Result;
+use crate::projects::actions::process_supervisor::ProcessSupervisor;
+use crate::projects::models::project::Project;
+use crate::projects::models::session::{ProjectSession, ProjectUsageStats};
+use tauri::{command, AppHandle, State};
+
+/// # Session Tracker Actions
+/// This module exposes the usage sessions recorded for the projects launched
+/// from the app, to know which projects are actually in use.
+
+/// Get the usage statistics of a project
+#[command]
+pub fn get_project_usage(
+    app_handle: AppHandle,
+    supervisor: State<'_, ProcessSupervisor>,
+    project_path: String,
+) -> Result<ProjectUsageStats> {
+    let project_id = Project::resolve_id(&app_handle, &project_path)?;
+    let sessions = ProjectSession::get_sessions(&app_handle)?;
+
+    Ok(ProjectUsageStats::compute(
+        &project_id,
+        &sessions,
+        &supervisor.running(),
+    ))
+}
+
+/// Get the finished usage sessions of a project, most recent first
+#[command]
+pub fn get_project_sessions(app_handle: AppHandle, project_path: String) -> Result<Vec<ProjectSession>> {
+    let project_id = Project::resolve_id(&app_handle, &project_path)?;
+    let mut sessions: Vec<ProjectSession> = ProjectSession::get_sessions(&app_handle)?
+        .into_iter()
+        .filter(|session| session.project_id == project_id)
+        .collect();
+
+    sessions.sort_by_key(|session| std::cmp::Reverse(session.started_at));
+
+    Ok(sessions)
+}
@@ -0,0 +1,234 @@
+use crate::env;
+use crate::misc::errors::Result;
+use crate::misc::errors::Verror::MessageError;
+use crate::projects::actions::archive_volumes;
+use crate::projects::actions::project_compressor::CompressionAlgorithm;
+use crate::projects::models::project::Project;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Predefined selections of the project files to archive
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum ArchivePreset {
+    SourceOnly,       // Project file, config and C++ code of the project and its plugins
+    SourceAndContent, // Source plus the assets of the project and its plugins
+    #[default]
+    Everything,       // The whole project directory
+}
+
+/// Rules selecting the files of a project archive, without deleting anything from the project.
+/// Patterns are globs matched against the path relative to the project directory ('/' separators),
+/// `*` does not cross directories while `**` does (e.g. "Saved/Logs/**", "**/*.pdb").
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ArchiveRules {
+    #[serde(default)]
+    pub preset: ArchivePreset,
+    #[serde(default)]
+    pub include: Vec<String>,    // Additional files to archive (only used with a preset that is not "Everything")
+    #[serde(default)]
+    pub exclude: Vec<String>,    // Files never archived, applied after the includes
+    #[serde(default)]
+    pub use_ignore_files: bool,  // Skip the files ignored by the .gitignore and .p4ignore files of the project
+}
+
+/// Description of the content of an archive, embedded in the archive as its last entry.
+/// It has no creation date so that the same files still produce the same archive.
+/// A differential backup only contains the files changed since its parent archive, the manifest
+/// completes them with the unchanged and deleted files so it describes the whole project.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    pub manifest_version: u32,
+    pub project_name: String,
+    pub engine_association: String,       // Engine version or GUID of the project ("Unknown" if it could not be read)
+    pub plugins: Vec<String>,             // Enabled plugins of the project
+    pub algorithm: CompressionAlgorithm,  // Settings the archive has been created with
+    pub rules: ArchiveRules,
+    #[serde(default)]
+    pub max_volume_size: Option<u64>,     // Maximum size of the volumes of a split archive
+    pub cleaned: bool,                    // True if the project has been cleaned before the compression
+    pub total_size: u64,                  // Size of the archived files in bytes
+    pub files: Vec<ManifestFile>,         // Archived files, in archive order
+    #[serde(default)]
+    pub parent: Option<String>,           // File name of the archive a differential backup applies to (None for a full archive)
+    #[serde(default)]
+    pub unchanged: Vec<ManifestFile>,     // Files of the parent snapshot that have not changed (not archived again)
+    #[serde(default)]
+    pub deleted: Vec<String>,             // Files of the parent snapshot deleted from the project
+}
+
+/// A file of an archive manifest
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestFile {
+    pub name: String,   // Path inside the archive
+    pub size: u64,      // Size in bytes
+    pub sha256: String, // SHA-256 of the content (lowercase hexadecimal)
+    #[serde(default)]
+    pub modified: u64,  // Last modification date of the file (milliseconds since UNIX epoch)
+}
+
+/// Compiled archive rules
+pub struct ArchiveFilter {
+    include: Option<GlobSet>, // None when every file is included
+    exclude: GlobSet,
+}
+
+impl ArchivePreset {
+    /// Files archived by the preset (empty for every file)
+    pub fn include_patterns(&self) -> &'static [&'static str] {
+        match self {
+            ArchivePreset::SourceOnly => &[
+                "*.uproject",
+                "Config/**",
+                "Source/**",
+                "Plugins/**/*.uplugin",
+                "Plugins/**/Config/**",
+                "Plugins/**/Source/**",
+                "Plugins/**/Resources/**",
+            ],
+            ArchivePreset::SourceAndContent => &[
+                "*.uproject",
+                "Config/**",
+                "Source/**",
+                "Content/**",
+                "Plugins/**",
+            ],
+            ArchivePreset::Everything => &[],
+        }
+    }
+
+    /// Files never archived by the preset, even when they match an include
+    pub fn exclude_patterns(&self) -> &'static [&'static str] {
+        match self {
+            ArchivePreset::SourceOnly | ArchivePreset::SourceAndContent => &[
+                "**/Binaries/**",
+                "**/Intermediate/**",
+                "**/DerivedDataCache/**",
+                "**/Saved/**",
+                "**/.vs/**",
+                "**/.idea/**",
+                "**/.git/**",
+            ],
+            ArchivePreset::Everything => &[],
+        }
+    }
+}
+
+impl ArchiveManifest {
+    /// Create the manifest of a project archive, the files are added by the archive writer
+    pub fn for_project(
+        project_path: &Path,
+        algorithm: &CompressionAlgorithm,
+        rules: &ArchiveRules,
+        max_volume_size: Option<u64>,
+        cleaned: bool,
+    ) -> ArchiveManifest {
+        let project = Project::try_from_path(&project_path.to_path_buf()).ok();
+
+        ArchiveManifest {
+            manifest_version: env::ARCHIVE_MANIFEST_VERSION,
+            project_name: project_path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("Unknown")
+                .to_string(),
+            engine_association: project
+                .as_ref()
+                .map(|project| project.engine_association.to_string())
+                .unwrap_or_else(|| "Unknown".to_string()),
+            plugins: project
+                .map(|project| {
+                    project
+                        .plugins
+                        .into_iter()
+                        .filter(|plugin| plugin.is_enabled)
+                        .map(|plugin| plugin.name)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            algorithm: algorithm.clone(),
+            rules: rules.clone(),
+            max_volume_size,
+            cleaned,
+            total_size: 0,
+            files: Vec::new(),
+            parent: None,
+            unchanged: Vec::new(),
+            deleted: Vec::new(),
+        }
+    }
+
+    /// Every file of the project when the archive has been created, sorted by name
+    pub fn snapshot(&self) -> Vec<ManifestFile> {
+        let mut snapshot: Vec<ManifestFile> = self.files.iter().chain(&self.unchanged).cloned().collect();
+        snapshot.sort_by(|a, b| a.name.cmp(&b.name));
+        snapshot
+    }
+
+    /// Path of the manifest written alongside an archive (e.g. "Project.zip.manifest.json"),
+    /// shared by all the volumes of a split archive
+    pub fn sidecar_path(archive_path: &Path) -> PathBuf {
+        let archive_path = archive_volumes::volume_base(archive_path).unwrap_or_else(|| archive_path.to_path_buf());
+        let mut file_name = archive_path.file_name().unwrap_or_default().to_os_string();
+        file_name.push(".manifest.json");
+        archive_path.with_file_name(file_name)
+    }
+}
+
+impl ArchiveFilter {
+    pub fn new(rules: &ArchiveRules) -> Result<Self> {
+        let include = match rules.preset {
+            ArchivePreset::Everything => None,
+            _ => Some(build_glob_set(
+                rules
+                    .preset
+                    .include_patterns()
+                    .iter()
+                    .copied()
+                    .chain(rules.include.iter().map(String::as_str)),
+            )?),
+        };
+        let exclude = build_glob_set(
+            rules
+                .preset
+                .exclude_patterns()
+                .iter()
+                .copied()
+                .chain(rules.exclude.iter().map(String::as_str)),
+        )?;
+
+        Ok(Self { include, exclude })
+    }
+
+    /// Check if a file or directory is excluded. The content of an excluded directory is not listed.
+    pub fn is_excluded(&self, name: &str, is_dir: bool) -> bool {
+        // "Saved/Logs/**" also excludes the "Saved/Logs" directory itself
+        self.exclude.is_match(name) || (is_dir && self.exclude.is_match(format!("{}/", name)))
+    }
+
+    /// Check if a file or directory that is not excluded is archived.
+    /// Directories are walked anyway, they are only archived if they match an include themselves.
+    pub fn is_included(&self, name: &str, is_dir: bool) -> bool {
+        match &self.include {
+            Some(include) => include.is_match(name) || (is_dir && include.is_match(format!("{}/", name))),
+            None => true,
+        }
+    }
+}
+
+fn build_glob_set<'a>(patterns: impl Iterator<Item = &'a str>) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+
+    for pattern in patterns.map(str::trim).filter(|pattern| !pattern.is_empty()) {
+        let glob = GlobBuilder::new(pattern.trim_start_matches('/'))
+            .literal_separator(true)
+            .case_insensitive(true)
+            .build()
+            .map_err(|e| MessageError(format!("Invalid archive rule \"{}\": {}", pattern, e)))?;
+        builder.add(glob);
+    }
+
+    builder
+        .build()
+        .map_err(|e| MessageError(format!("Invalid archive rules: {}", e)))
+}
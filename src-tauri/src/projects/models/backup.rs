@@ -0,0 +1,511 @@
+use crate::env;
+use crate::misc::errors::Result;
+use crate::misc::errors::Verror::MessageError;
+use crate::settings::models::settings::RetentionPolicy;
+use chrono::{Datelike, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Timelike};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashSet;
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+/// A cron-like schedule: "minute hour day-of-month month day-of-week".
+/// Each field accepts `*`, values, ranges (`1-5`), lists (`1,15`) and steps (`*/15`, `0-30/10`).
+/// The days of the week go from 0 (Sunday) to 6 (7 is also Sunday). Like cron, when both the day
+/// of the month and the day of the week are restricted, a day matching either of them matches.
+#[derive(Debug, Clone)]
+pub struct CronSchedule {
+    minutes: u64,          // Bit n set if the minute n matches
+    hours: u64,
+    days_of_month: u64,    // Bits 1 to 31
+    months: u64,           // Bits 1 to 12
+    weekdays: u64,         // Bits 0 (Sunday) to 6
+    any_day_of_month: bool,
+    any_weekday: bool,
+}
+
+/// Scheduler state of the backup schedule of a project, persisted in the store
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupState {
+    pub project_id: String,           // Project of the schedule (see `Project::id`)
+    pub scheduled_since: u64,         // Date the scheduler found the schedule (seconds since UNIX epoch)
+    pub last_run: Option<u64>,        // Date of the last backup, successful or cancelled by the user
+    pub retry_at: Option<u64>,        // Date a failed backup is tried again
+    pub last_error: Option<String>,   // Error of the last backup (None if it succeeded)
+    pub archives: Vec<BackupArchive>, // Archives created by the schedule that have not been pruned, oldest first
+}
+
+/// An archive created by a backup schedule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupArchive {
+    pub path: String,    // Archive file (first volume of a split archive)
+    pub created_at: u64, // Seconds since UNIX epoch
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<CronSchedule> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minutes, hours, days_of_month, months, weekdays] = fields[..] else {
+            return Err(MessageError(format!(
+                "Invalid schedule \"{}\": expected 5 fields (minute hour day-of-month month day-of-week)",
+                expression
+            )));
+        };
+
+        // 7 is an alias of Sunday
+        let mut weekday_bits = parse_field(weekdays, 0, 7)?;
+        if weekday_bits & (1 << 7) != 0 {
+            weekday_bits = (weekday_bits | 1) & !(1 << 7);
+        }
+
+        Ok(CronSchedule {
+            minutes: parse_field(minutes, 0, 59)?,
+            hours: parse_field(hours, 0, 23)?,
+            days_of_month: parse_field(days_of_month, 1, 31)?,
+            months: parse_field(months, 1, 12)?,
+            weekdays: weekday_bits,
+            any_day_of_month: days_of_month.starts_with('*'),
+            any_weekday: weekdays.starts_with('*'),
+        })
+    }
+
+    /// First matching minute strictly after a date, None if the schedule never matches
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let start = after.with_second(0)?.with_nanosecond(0)? + chrono::Duration::minutes(1);
+        let mut date = start.date();
+
+        for _ in 0..env::BACKUP_SCHEDULE_LOOKAHEAD_DAYS {
+            if has_bit(self.months, date.month()) && self.matches_day(date) {
+                let first = if date == start.date() { start.time() } else { NaiveTime::MIN };
+                for hour in first.hour()..24 {
+                    if !has_bit(self.hours, hour) {
+                        continue;
+                    }
+                    let first_minute = if hour == first.hour() { first.minute() } else { 0 };
+                    if let Some(minute) = (first_minute..60).find(|minute| has_bit(self.minutes, *minute)) {
+                        return date.and_hms_opt(hour, minute, 0);
+                    }
+                }
+            }
+            date = date.succ_opt()?;
+        }
+
+        None
+    }
+
+    fn matches_day(&self, date: NaiveDate) -> bool {
+        let day_of_month = has_bit(self.days_of_month, date.day());
+        let weekday = has_bit(self.weekdays, date.weekday().num_days_from_sunday());
+
+        match (self.any_day_of_month, self.any_weekday) {
+            (true, true) => true,
+            (true, false) => weekday,
+            (false, true) => day_of_month,
+            (false, false) => day_of_month || weekday,
+        }
+    }
+}
+
+impl BackupState {
+    pub fn new(project_id: &str, now: u64) -> BackupState {
+        BackupState {
+            project_id: project_id.to_string(),
+            scheduled_since: now,
+            last_run: None,
+            retry_at: None,
+            last_error: None,
+            archives: Vec::new(),
+        }
+    }
+
+    pub fn get_states(app_handle: &AppHandle) -> Result<Vec<BackupState>> {
+        let store = app_handle.store(env::STORE_FILE_NAME)?;
+
+        let states = serde_json::from_value::<Vec<BackupState>>(
+            store.get(env::STORE_BACKUP_STATES_KEY).unwrap_or(json!([])),
+        )?;
+
+        Ok(states)
+    }
+
+    pub fn save_states(app_handle: &AppHandle, states: &[BackupState]) -> Result<()> {
+        let store = app_handle.store(env::STORE_FILE_NAME)?;
+        store.set(env::STORE_BACKUP_STATES_KEY, serde_json::to_value(states)?);
+        store.save()?;
+
+        Ok(())
+    }
+
+    /// Take over the state of another schedule (e.g. when a project is relinked to a path where another
+    /// project with its own schedule was tracked): its archives are added, oldest first, and the later run is kept.
+    pub fn merge(&mut self, other: BackupState) {
+        for archive in other.archives {
+            if !self.archives.iter().any(|known| known.path == archive.path) {
+                self.archives.push(archive);
+            }
+        }
+        self.archives.sort_by_key(|archive| archive.created_at);
+
+        self.scheduled_since = self.scheduled_since.min(other.scheduled_since);
+        if other.last_run > self.last_run {
+            self.last_run = other.last_run;
+            self.retry_at = other.retry_at;
+            self.last_error = other.last_error;
+        }
+    }
+
+    /// Date of the next backup (seconds since UNIX epoch). A run missed while the application
+    /// was closed is in the past, so it is caught up once and the schedule resumes from there.
+    pub fn next_run(&self, schedule: &CronSchedule) -> Option<u64> {
+        let reference = self.last_run.unwrap_or(self.scheduled_since);
+        let mut after = Local.timestamp_opt(reference as i64, 0).single()?.naive_local();
+
+        // A local time skipped by a daylight saving change does not exist, the next match is used
+        for _ in 0..2 {
+            let next = schedule.next_after(after)?;
+            if let Some(next) = Local.from_local_datetime(&next).earliest() {
+                return Some(next.timestamp().max(0) as u64);
+            }
+            after = next;
+        }
+
+        None
+    }
+
+    /// Check if a backup has to run now
+    pub fn is_due(&self, schedule: &CronSchedule, now: u64) -> bool {
+        let waiting_retry = self.retry_at.map(|retry_at| retry_at > now).unwrap_or(false);
+        !waiting_retry && self.next_run(schedule).map(|next| next <= now).unwrap_or(false)
+    }
+
+    /// Archives the retention policy does not keep anymore. The most recent archive is always kept.
+    pub fn pruned_archives(&self, policy: &RetentionPolicy, now: u64) -> Vec<BackupArchive> {
+        if policy.keep_last.is_none() && policy.keep_daily.is_none() && policy.keep_weekly.is_none() {
+            return Vec::new();
+        }
+
+        let mut newest_first: Vec<&BackupArchive> = self.archives.iter().collect();
+        newest_first.sort_by_key(|archive| std::cmp::Reverse(archive.created_at));
+
+        let local_date = |timestamp: u64| {
+            Local
+                .timestamp_opt(timestamp as i64, 0)
+                .single()
+                .map(|date| date.date_naive())
+                .unwrap_or_default()
+        };
+        let week_start =
+            |date: NaiveDate| date - chrono::Duration::days(date.weekday().num_days_from_monday() as i64);
+        let today = local_date(now);
+
+        let mut kept: HashSet<&str> = HashSet::new();
+        if let Some(newest) = newest_first.first() {
+            kept.insert(&newest.path);
+        }
+        kept.extend(
+            newest_first
+                .iter()
+                .take(policy.keep_last.unwrap_or(0))
+                .map(|archive| archive.path.as_str()),
+        );
+
+        // The most recent archive of each day and each week of the period
+        let mut days = HashSet::new();
+        let mut weeks = HashSet::new();
+        for archive in &newest_first {
+            let date = local_date(archive.created_at);
+
+            let age_days = (today - date).num_days();
+            if age_days < policy.keep_daily.unwrap_or(0) as i64 && days.insert(date) {
+                kept.insert(&archive.path);
+            }
+
+            let age_weeks = (week_start(today) - week_start(date)).num_days() / 7;
+            if age_weeks < policy.keep_weekly.unwrap_or(0) as i64 && weeks.insert(week_start(date)) {
+                kept.insert(&archive.path);
+            }
+        }
+
+        self.archives
+            .iter()
+            .filter(|archive| !kept.contains(archive.path.as_str()))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Parse a cron field to a bit set of the values it matches
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64> {
+    let invalid = || MessageError(format!("Invalid schedule field \"{}\" (values from {} to {})", field, min, max));
+    let parse_value = |value: &str| -> Result<u32> {
+        value
+            .parse::<u32>()
+            .ok()
+            .filter(|value| (min..=max).contains(value))
+            .ok_or_else(invalid)
+    };
+
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().ok().filter(|step| *step > 0).ok_or_else(invalid)?),
+            None => (part, 1),
+        };
+
+        let (first, last) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((first, last)) => (parse_value(first)?, parse_value(last)?),
+            // "5/15" starts at 5 and goes to the end of the field
+            None if part.contains('/') => (parse_value(range)?, max),
+            None => {
+                let value = parse_value(range)?;
+                (value, value)
+            }
+        };
+        if first > last {
+            return Err(invalid());
+        }
+
+        for value in (first..=last).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+
+    Ok(bits)
+}
+
+fn has_bit(bits: u64, value: u32) -> bool {
+    bits & (1 << value) != 0
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(year, month, day)
+            .and_then(|date| date.and_hms_opt(hour, minute, 0))
+            .unwrap()
+    }
+
+    /// Timestamp of a local date (the tests use times no daylight saving change skips)
+    fn local(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> u64 {
+        Local
+            .from_local_datetime(&at(year, month, day, hour, minute))
+            .earliest()
+            .unwrap()
+            .timestamp() as u64
+    }
+
+    fn bits(values: &[u32]) -> u64 {
+        values.iter().fold(0, |bits, value| bits | 1 << value)
+    }
+
+    fn state_with_archives(created_at: &[u64]) -> BackupState {
+        let mut state = BackupState::new("3f6c1a52-8d2e-4b7a-9c41-5e0d2f8a6b13", 0);
+        state.archives = created_at
+            .iter()
+            .enumerate()
+            .map(|(index, created_at)| BackupArchive {
+                path: format!("archive_{}", index),
+                created_at: *created_at,
+            })
+            .collect();
+        state
+    }
+
+    fn pruned(state: &BackupState, policy: RetentionPolicy, now: u64) -> Vec<String> {
+        state.pruned_archives(&policy, now).into_iter().map(|archive| archive.path).collect()
+    }
+
+    #[test]
+    fn parses_steps_ranges_and_lists() {
+        let schedule = CronSchedule::parse("*/15 9-17 1,15 */2 *").unwrap();
+        assert_eq!(schedule.minutes, bits(&[0, 15, 30, 45]));
+        assert_eq!(schedule.hours, bits(&[9, 10, 11, 12, 13, 14, 15, 16, 17]));
+        assert_eq!(schedule.days_of_month, bits(&[1, 15]));
+        assert_eq!(schedule.months, bits(&[1, 3, 5, 7, 9, 11]));
+        assert!(schedule.any_weekday && !schedule.any_day_of_month);
+
+        let schedule = CronSchedule::parse("0-30/10 5/6 * * 1-3,5").unwrap();
+        assert_eq!(schedule.minutes, bits(&[0, 10, 20, 30]));
+        assert_eq!(schedule.hours, bits(&[5, 11, 17, 23]));
+        assert_eq!(schedule.weekdays, bits(&[1, 2, 3, 5]));
+
+        // 7 is Sunday
+        assert_eq!(CronSchedule::parse("0 0 * * 7").unwrap().weekdays, bits(&[0]));
+        assert_eq!(CronSchedule::parse("0 0 * * 5-7").unwrap().weekdays, bits(&[0, 5, 6]));
+    }
+
+    #[test]
+    fn refuses_invalid_expressions() {
+        for expression in [
+            "",
+            "* * * *",
+            "* * * * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "* * * 13 *",
+            "* * * * 8",
+            "*/0 * * * *",
+            "30-10 * * * *",
+            "a * * * *",
+            "1,,2 * * * *",
+        ] {
+            assert!(CronSchedule::parse(expression).is_err(), "{}", expression);
+        }
+    }
+
+    #[test]
+    fn finds_the_next_matching_minute() {
+        let schedule = CronSchedule::parse("30 2 * * *").unwrap();
+        assert_eq!(schedule.next_after(at(2024, 9, 1, 1, 0)), Some(at(2024, 9, 1, 2, 30)));
+        // Strictly after, the seconds are ignored
+        assert_eq!(schedule.next_after(at(2024, 9, 1, 2, 30)), Some(at(2024, 9, 2, 2, 30)));
+        assert_eq!(
+            schedule.next_after(at(2024, 9, 1, 2, 29).with_second(59).unwrap()),
+            Some(at(2024, 9, 1, 2, 30))
+        );
+
+        let schedule = CronSchedule::parse("0 0 1 1 *").unwrap();
+        assert_eq!(schedule.next_after(at(2024, 6, 1, 0, 0)), Some(at(2025, 1, 1, 0, 0)));
+
+        let schedule = CronSchedule::parse("0 12 29 2 *").unwrap();
+        assert_eq!(schedule.next_after(at(2025, 1, 1, 0, 0)), Some(at(2028, 2, 29, 12, 0)));
+
+        // February never has 31 days
+        assert_eq!(CronSchedule::parse("0 0 31 2 *").unwrap().next_after(at(2024, 1, 1, 0, 0)), None);
+    }
+
+    #[test]
+    fn matches_the_day_of_month_or_the_day_of_week() {
+        // 2024-09-01 is a Sunday, the 6th and the 13th are Fridays
+        let either = CronSchedule::parse("0 12 10 * 5").unwrap();
+        assert_eq!(either.next_after(at(2024, 9, 1, 0, 0)), Some(at(2024, 9, 6, 12, 0)));
+        assert_eq!(either.next_after(at(2024, 9, 6, 12, 0)), Some(at(2024, 9, 10, 12, 0)));
+        assert_eq!(either.next_after(at(2024, 9, 10, 12, 0)), Some(at(2024, 9, 13, 12, 0)));
+
+        // A "*" field does not match every day
+        let day_of_month = CronSchedule::parse("0 12 10 * *").unwrap();
+        assert_eq!(day_of_month.next_after(at(2024, 9, 1, 0, 0)), Some(at(2024, 9, 10, 12, 0)));
+        let weekday = CronSchedule::parse("0 12 * * 5").unwrap();
+        assert_eq!(weekday.next_after(at(2024, 9, 6, 12, 0)), Some(at(2024, 9, 13, 12, 0)));
+    }
+
+    #[test]
+    fn catches_up_a_missed_run_once() {
+        let schedule = CronSchedule::parse("0 3 * * *").unwrap();
+        let mut state = BackupState::new("3f6c1a52-8d2e-4b7a-9c41-5e0d2f8a6b13", local(2024, 9, 1, 12, 0));
+
+        // First run after the schedule has been found
+        assert_eq!(state.next_run(&schedule), Some(local(2024, 9, 2, 3, 0)));
+        assert!(!state.is_due(&schedule, local(2024, 9, 2, 2, 59)));
+        assert!(state.is_due(&schedule, local(2024, 9, 2, 3, 0)));
+
+        // The application was closed for days: the missed runs are caught up by a single backup
+        state.last_run = Some(local(2024, 9, 2, 3, 0));
+        let now = local(2024, 9, 5, 12, 0);
+        assert!(state.is_due(&schedule, now));
+        state.last_run = Some(now);
+        assert!(!state.is_due(&schedule, now));
+        assert_eq!(state.next_run(&schedule), Some(local(2024, 9, 6, 3, 0)));
+
+        // A failed backup waits for its retry
+        let now = local(2024, 9, 6, 3, 5);
+        state.retry_at = Some(local(2024, 9, 6, 3, 30));
+        assert!(!state.is_due(&schedule, now));
+        assert!(state.is_due(&schedule, local(2024, 9, 6, 3, 30)));
+    }
+
+    #[test]
+    fn merges_the_archives_and_the_later_run() {
+        let mut state = state_with_archives(&[local(2024, 9, 1, 12, 0), local(2024, 9, 4, 12, 0)]);
+        state.last_run = Some(local(2024, 9, 4, 12, 0));
+        let mut other = state_with_archives(&[local(2024, 9, 2, 12, 0), local(2024, 9, 5, 12, 0)]);
+        for archive in other.archives.iter_mut() {
+            archive.path = format!("other_{}", archive.path);
+        }
+        other.archives.push(state.archives[0].clone());
+        other.last_run = Some(local(2024, 9, 5, 12, 0));
+        other.last_error = Some("Disk full".to_string());
+
+        state.merge(other);
+        let archives: Vec<&str> = state.archives.iter().map(|archive| archive.path.as_str()).collect();
+        assert_eq!(archives, ["archive_0", "other_archive_0", "archive_1", "other_archive_1"]);
+        assert_eq!(state.last_run, Some(local(2024, 9, 5, 12, 0)));
+        assert_eq!(state.last_error.as_deref(), Some("Disk full"));
+
+        // An earlier run does not replace the state of the last one
+        let mut earlier = state_with_archives(&[]);
+        earlier.last_run = Some(local(2024, 9, 3, 12, 0));
+        state.merge(earlier);
+        assert_eq!(state.last_run, Some(local(2024, 9, 5, 12, 0)));
+        assert_eq!(state.archives.len(), 4);
+    }
+
+    #[test]
+    fn keeps_everything_without_a_rule() {
+        let state = state_with_archives(&[local(2024, 9, 1, 12, 0), local(2024, 9, 2, 12, 0)]);
+        assert!(pruned(&state, RetentionPolicy::default(), local(2024, 9, 30, 12, 0)).is_empty());
+    }
+
+    #[test]
+    fn keeps_the_last_archives() {
+        let state = state_with_archives(&[
+            local(2024, 9, 1, 12, 0),
+            local(2024, 9, 2, 12, 0),
+            local(2024, 9, 3, 12, 0),
+            local(2024, 9, 4, 12, 0),
+        ]);
+        let policy = RetentionPolicy { keep_last: Some(2), ..Default::default() };
+        assert_eq!(pruned(&state, policy, local(2024, 9, 4, 18, 0)), ["archive_0", "archive_1"]);
+    }
+
+    #[test]
+    fn keeps_the_newest_archive_of_each_day() {
+        let state = state_with_archives(&[
+            local(2024, 9, 6, 12, 0), // 5 days ago
+            local(2024, 9, 9, 12, 0), // 2 days ago
+            local(2024, 9, 10, 9, 0),
+            local(2024, 9, 10, 12, 0), // Yesterday
+            local(2024, 9, 11, 11, 0),
+            local(2024, 9, 11, 12, 0), // Today
+        ]);
+        let policy = RetentionPolicy { keep_daily: Some(3), ..Default::default() };
+        assert_eq!(
+            pruned(&state, policy, local(2024, 9, 11, 18, 0)),
+            ["archive_0", "archive_2", "archive_4"]
+        );
+    }
+
+    #[test]
+    fn keeps_the_newest_archive_of_each_week() {
+        // 2024-09-11 is a Wednesday, the weeks start on Monday
+        let state = state_with_archives(&[
+            local(2024, 8, 28, 12, 0), // 2 weeks ago
+            local(2024, 9, 2, 12, 0),
+            local(2024, 9, 4, 12, 0), // Last week
+            local(2024, 9, 10, 12, 0),
+            local(2024, 9, 11, 12, 0), // This week
+        ]);
+        let now = local(2024, 9, 11, 18, 0);
+
+        let weekly = RetentionPolicy { keep_weekly: Some(2), ..Default::default() };
+        assert_eq!(pruned(&state, weekly, now), ["archive_0", "archive_1", "archive_3"]);
+
+        // An archive kept by any rule is kept
+        let combined = RetentionPolicy { keep_last: Some(2), keep_weekly: Some(2), ..Default::default() };
+        assert_eq!(pruned(&state, combined, now), ["archive_0", "archive_1"]);
+        let combined = RetentionPolicy { keep_last: Some(1), keep_daily: Some(2), keep_weekly: Some(3) };
+        assert_eq!(pruned(&state, combined, now), ["archive_1"]);
+    }
+
+    #[test]
+    fn always_keeps_the_newest_archive() {
+        let state = state_with_archives(&[local(2024, 8, 1, 12, 0), local(2024, 8, 2, 12, 0)]);
+        let policy = RetentionPolicy { keep_daily: Some(1), ..Default::default() };
+        assert_eq!(pruned(&state, policy, local(2024, 9, 11, 12, 0)), ["archive_0"]);
+    }
+}
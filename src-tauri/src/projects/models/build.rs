@@ -0,0 +1,362 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+lazy_static! {
+    /// MSVC and UnrealHeaderTool: `File.cpp(12,5): error C2065: message` (column optional)
+    static ref MSVC_DIAGNOSTIC: Regex = Regex::new(
+        r"^\s*(?P<file>.+?)\((?P<line>\d+)(?:,(?P<column>\d+))?\)\s*:\s*(?:fatal )?(?P<severity>(?i:error|warning))(?:\s+(?P<code>[A-Z]+\d+))?\s*:\s*(?P<message>.*)$"
+    ).unwrap();
+
+    /// Clang and GCC: `/path/File.cpp:12:5: error: message [-Wflag]`
+    static ref CLANG_DIAGNOSTIC: Regex = Regex::new(
+        r"^\s*(?P<file>.+?):(?P<line>\d+):(?P<column>\d+):\s*(?:fatal )?(?P<severity>error|warning):\s*(?P<message>.*?)(?:\s*\[(?P<code>-W[^\]]+)\])?$"
+    ).unwrap();
+
+    /// Linker and tools without a line: `Module.obj : error LNK2019: message`
+    static ref TOOL_DIAGNOSTIC: Regex = Regex::new(
+        r"^\s*(?P<file>.+?)\s*:\s*(?:fatal )?(?P<severity>error|warning)\s+(?P<code>[A-Z]+\d+)\s*:\s*(?P<message>.*)$"
+    ).unwrap();
+
+    /// UnrealBuildTool itself: `ERROR: message` / `WARNING: message`
+    static ref UBT_DIAGNOSTIC: Regex = Regex::new(
+        r"^\s*(?P<severity>ERROR|WARNING|(?:UnrealBuildTool\s*:\s*)?(?:error|warning))\s*:\s*(?P<message>.+)$"
+    ).unwrap();
+}
+
+/// Kind of target compiled by UnrealBuildTool
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum BuildTarget {
+    Editor, // Editor modules of the project (<Project>Editor target)
+    Game,   // Standalone game (<Project> target)
+}
+
+/// Build configuration passed to UnrealBuildTool
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum BuildConfiguration {
+    Debug,
+    DebugGame,
+    Development,
+    Shipping,
+    Test,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildRequest {
+    pub project_path: String,
+    pub target: BuildTarget,
+    pub configuration: BuildConfiguration,
+    pub platform: Option<String>, // UBT platform name (Win64, Mac, Linux...), the current platform if None
+    #[serde(default)]
+    pub extra_arguments: Vec<String>, // Additional arguments passed to UnrealBuildTool
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildResult {
+    pub project_path: String,
+    pub target_name: String,      // Name of the compiled target (e.g. "MyGameEditor")
+    pub platform: String,
+    pub configuration: BuildConfiguration,
+    pub success: bool,
+    pub exit_code: Option<i32>,   // None if UnrealBuildTool has been terminated by a signal
+    pub duration_ms: u128,
+    pub command_line: String,     // Full command line UnrealBuildTool has been run with
+    pub report: BuildReport,      // Errors and warnings found in the output
+}
+
+/// Severity of a compiler diagnostic
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+}
+
+/// An error or a warning found in the output of UnrealBuildTool
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BuildDiagnostic {
+    pub severity: DiagnosticSeverity,
+    pub file: Option<String>,  // Source file (None for messages of UBT itself)
+    pub line: Option<u32>,     // 1-based line in the file
+    pub column: Option<u32>,   // 1-based column in the line
+    pub code: Option<String>,  // Compiler code (C2065, LNK2019, -Wunused-variable...)
+    pub message: String,
+}
+
+/// Diagnostics of a build, deduplicated in the order they have been reported
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BuildReport {
+    pub error_count: usize,
+    pub warning_count: usize,
+    pub diagnostics: Vec<BuildDiagnostic>,
+}
+
+impl BuildTarget {
+    /// Returns the name of the target of a project (e.g. "MyGameEditor" for the editor of MyGame)
+    pub fn target_name(&self, project_name: &str) -> String {
+        match self {
+            BuildTarget::Editor => format!("{}Editor", project_name),
+            BuildTarget::Game => project_name.to_string(),
+        }
+    }
+}
+
+impl BuildConfiguration {
+    pub fn as_str(&self) -> &str {
+        match self {
+            BuildConfiguration::Debug => "Debug",
+            BuildConfiguration::DebugGame => "DebugGame",
+            BuildConfiguration::Development => "Development",
+            BuildConfiguration::Shipping => "Shipping",
+            BuildConfiguration::Test => "Test",
+        }
+    }
+}
+
+impl BuildDiagnostic {
+    /// Parses a line of UBT, MSVC or clang output, returns None if it is not an error or a warning
+    pub fn parse(line: &str) -> Option<BuildDiagnostic> {
+        let captures = [&*MSVC_DIAGNOSTIC, &*CLANG_DIAGNOSTIC, &*TOOL_DIAGNOSTIC, &*UBT_DIAGNOSTIC]
+            .iter()
+            .find_map(|pattern| pattern.captures(line))?;
+
+        let severity = if captures["severity"].to_lowercase().ends_with("error") {
+            DiagnosticSeverity::Error
+        } else {
+            DiagnosticSeverity::Warning
+        };
+        let number = |name: &str| captures.name(name).and_then(|m| m.as_str().parse::<u32>().ok());
+
+        Some(BuildDiagnostic {
+            severity,
+            file: captures.name("file").map(|m| m.as_str().trim().to_string()),
+            line: number("line"),
+            column: number("column"),
+            code: captures.name("code").map(|m| m.as_str().to_string()),
+            message: captures["message"].trim().to_string(),
+        })
+    }
+}
+
+impl BuildReport {
+    /// Adds a diagnostic to the report, returns false if it has already been reported
+    pub fn add(&mut self, diagnostic: BuildDiagnostic) -> bool {
+        // Compilers and UBT often print the same diagnostic several times
+        if self.diagnostics.contains(&diagnostic) {
+            return false;
+        }
+
+        match diagnostic.severity {
+            DiagnosticSeverity::Error => self.error_count += 1,
+            DiagnosticSeverity::Warning => self.warning_count += 1,
+        }
+        self.diagnostics.push(diagnostic);
+
+        true
+    }
+}
+
+/// Returns the UnrealBuildTool name of the platform the app runs on
+pub fn host_platform() -> &'static str {
+    if cfg!(target_os = "windows") {
+        "Win64"
+    } else if cfg!(target_os = "macos") {
+        "Mac"
+    } else {
+        "Linux"
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnostic(
+        severity: DiagnosticSeverity,
+        file: Option<&str>,
+        line: Option<u32>,
+        column: Option<u32>,
+        code: Option<&str>,
+        message: &str,
+    ) -> BuildDiagnostic {
+        BuildDiagnostic {
+            severity,
+            file: file.map(str::to_string),
+            line,
+            column,
+            code: code.map(str::to_string),
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_msvc_lines() {
+        assert_eq!(
+            BuildDiagnostic::parse(r"D:\Projects\MyGame\Source\MyGame\MyActor.cpp(42,17): error C2065: 'Foo': undeclared identifier"),
+            Some(diagnostic(
+                DiagnosticSeverity::Error,
+                Some(r"D:\Projects\MyGame\Source\MyGame\MyActor.cpp"),
+                Some(42),
+                Some(17),
+                Some("C2065"),
+                "'Foo': undeclared identifier",
+            ))
+        );
+        assert_eq!(
+            BuildDiagnostic::parse(r"  D:\Projects\MyGame\Source\MyGame\MyActor.h(12): warning C4996: 'FBar::Baz': was declared deprecated"),
+            Some(diagnostic(
+                DiagnosticSeverity::Warning,
+                Some(r"D:\Projects\MyGame\Source\MyGame\MyActor.h"),
+                Some(12),
+                None,
+                Some("C4996"),
+                "'FBar::Baz': was declared deprecated",
+            ))
+        );
+        assert_eq!(
+            BuildDiagnostic::parse(r"D:\Projects\MyGame\Source\MyGame\MyActor.cpp(3): fatal error C1083: Cannot open include file: 'Missing.h': No such file or directory"),
+            Some(diagnostic(
+                DiagnosticSeverity::Error,
+                Some(r"D:\Projects\MyGame\Source\MyGame\MyActor.cpp"),
+                Some(3),
+                None,
+                Some("C1083"),
+                "Cannot open include file: 'Missing.h': No such file or directory",
+            ))
+        );
+    }
+
+    #[test]
+    fn parses_unreal_header_tool_lines() {
+        assert_eq!(
+            BuildDiagnostic::parse(r"D:\Projects\MyGame\Source\MyGame\MyActor.h(25): Error: Unrecognized type 'FMissing' - type must be a UCLASS, USTRUCT or UENUM"),
+            Some(diagnostic(
+                DiagnosticSeverity::Error,
+                Some(r"D:\Projects\MyGame\Source\MyGame\MyActor.h"),
+                Some(25),
+                None,
+                None,
+                "Unrecognized type 'FMissing' - type must be a UCLASS, USTRUCT or UENUM",
+            ))
+        );
+    }
+
+    #[test]
+    fn parses_clang_lines() {
+        assert_eq!(
+            BuildDiagnostic::parse("/home/user/MyGame/Source/MyGame/MyActor.cpp:42:17: error: use of undeclared identifier 'Foo'"),
+            Some(diagnostic(
+                DiagnosticSeverity::Error,
+                Some("/home/user/MyGame/Source/MyGame/MyActor.cpp"),
+                Some(42),
+                Some(17),
+                None,
+                "use of undeclared identifier 'Foo'",
+            ))
+        );
+        assert_eq!(
+            BuildDiagnostic::parse("/home/user/MyGame/Source/MyGame/MyActor.cpp:8:9: warning: unused variable 'Count' [-Wunused-variable]"),
+            Some(diagnostic(
+                DiagnosticSeverity::Warning,
+                Some("/home/user/MyGame/Source/MyGame/MyActor.cpp"),
+                Some(8),
+                Some(9),
+                Some("-Wunused-variable"),
+                "unused variable 'Count'",
+            ))
+        );
+        // Windows paths hold a colon before the line
+        assert_eq!(
+            BuildDiagnostic::parse(r"C:\Projects\MyGame\Source\MyGame\MyActor.cpp:3:10: fatal error: 'Missing.h' file not found"),
+            Some(diagnostic(
+                DiagnosticSeverity::Error,
+                Some(r"C:\Projects\MyGame\Source\MyGame\MyActor.cpp"),
+                Some(3),
+                Some(10),
+                None,
+                "'Missing.h' file not found",
+            ))
+        );
+    }
+
+    #[test]
+    fn parses_linker_lines() {
+        assert_eq!(
+            BuildDiagnostic::parse(r#"MyActor.cpp.obj : error LNK2019: unresolved external symbol "public: void __cdecl UFoo::Bar(int)" (?Bar@UFoo@@QEAAXH@Z) referenced in function "public: virtual void __cdecl AMyActor::BeginPlay(void)""#),
+            Some(diagnostic(
+                DiagnosticSeverity::Error,
+                Some("MyActor.cpp.obj"),
+                None,
+                None,
+                Some("LNK2019"),
+                r#"unresolved external symbol "public: void __cdecl UFoo::Bar(int)" (?Bar@UFoo@@QEAAXH@Z) referenced in function "public: virtual void __cdecl AMyActor::BeginPlay(void)""#,
+            ))
+        );
+        assert_eq!(
+            BuildDiagnostic::parse("LINK : fatal error LNK1181: cannot open input file 'Missing.lib'"),
+            Some(diagnostic(
+                DiagnosticSeverity::Error,
+                Some("LINK"),
+                None,
+                None,
+                Some("LNK1181"),
+                "cannot open input file 'Missing.lib'",
+            ))
+        );
+    }
+
+    #[test]
+    fn parses_unreal_build_tool_lines() {
+        assert_eq!(
+            BuildDiagnostic::parse("ERROR: Unable to find target 'MyGameEditor'"),
+            Some(diagnostic(DiagnosticSeverity::Error, None, None, None, None, "Unable to find target 'MyGameEditor'"))
+        );
+        assert_eq!(
+            BuildDiagnostic::parse("WARNING: Plugin 'OldPlugin' is not compatible with this engine version"),
+            Some(diagnostic(
+                DiagnosticSeverity::Warning,
+                None,
+                None,
+                None,
+                None,
+                "Plugin 'OldPlugin' is not compatible with this engine version",
+            ))
+        );
+        assert_eq!(
+            BuildDiagnostic::parse("UnrealBuildTool : error : Unhandled exception: Could not find the project"),
+            Some(diagnostic(
+                DiagnosticSeverity::Error,
+                None,
+                None,
+                None,
+                None,
+                "Unhandled exception: Could not find the project",
+            ))
+        );
+    }
+
+    #[test]
+    fn ignores_other_lines() {
+        for line in [
+            "",
+            "Building MyGameEditor...",
+            "[1/12] Compile MyActor.cpp",
+            "Using 'git status' to determine working set for adaptive non-unity build.",
+            "Total execution time: 12.34 seconds",
+            "Log file: C:\\Users\\user\\AppData\\Local\\UnrealBuildTool\\Log.txt",
+        ] {
+            assert_eq!(BuildDiagnostic::parse(line), None, "{}", line);
+        }
+    }
+
+    #[test]
+    fn reports_each_diagnostic_once() {
+        let mut report = BuildReport::default();
+        let line = "/home/user/MyGame/Source/MyGame/MyActor.cpp:8:9: warning: unused variable 'Count' [-Wunused-variable]";
+
+        assert!(report.add(BuildDiagnostic::parse(line).unwrap()));
+        assert!(!report.add(BuildDiagnostic::parse(line).unwrap()));
+        assert!(report.add(BuildDiagnostic::parse("ERROR: Unable to find target 'MyGameEditor'").unwrap()));
+        assert_eq!((report.error_count, report.warning_count, report.diagnostics.len()), (1, 1, 2));
+    }
+}
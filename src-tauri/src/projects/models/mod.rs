@@ -1,2 +1,7 @@
 pub mod project;
-pub mod plugins;
\ No newline at end of file
+pub mod plugins;
+pub mod process;
+pub mod session;
+pub mod build;
+pub mod archive;
+pub mod backup;
\ No newline at end of file
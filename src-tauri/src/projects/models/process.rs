@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+/// Kind of process launched by the application
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ProcessKind {
+    Editor, // Unreal Editor opened on a project
+    Game,   // Standalone game instance (-game)
+    Server, // Dedicated server instance (-server)
+    Ide,    // IDE opened on a project or an engine
+}
+
+/// A process launched by the application and still running
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunningProcess {
+    pub pid: u32,                       // Process id
+    pub kind: ProcessKind,              // What has been launched
+    pub project_path: Option<String>,   // Project the process has been launched for (.uproject file)
+    #[serde(default)]
+    pub project_id: Option<String>,     // Identifier of that project (None if it is not tracked)
+    pub group_id: Option<String>,       // Group of processes launched together (e.g. a network test)
+    pub command_line: String,           // Full command line of the process
+    pub engine_version: Option<String>, // Version of the engine the process runs on (None if unknown)
+    pub started_at: u64,                // Start date (seconds since UNIX epoch)
+}
@@ -2,26 +2,90 @@ use crate::env;
 use crate::misc::errors::ErrorLevel;
 use crate::misc::payloads::ProjectsUpdatedPayload;
 use crate::misc::prelude::log;
+use crate::misc::tasks::CancellationToken;
+use crate::projects::actions::process_supervisor::ProcessSupervisor;
 use crate::projects::models::plugins::{ProjectPlugin, UprojectPluginEntry};
+use crate::projects::models::session::{ProjectSession, ProjectUsageStats};
 use log::error;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
-use std::path::PathBuf;
-use tauri::Emitter;
+use std::path::{Path, PathBuf};
+use tauri::{Emitter, Manager};
 use tauri_plugin_store::StoreExt;
+use uuid::Uuid;
 
 /// Represents the association of a project with a specific Unreal Engine version.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum EngineAssociation {
     Standard(String), // For standard version (4.27, 5.0, etc.)
+    Guid(String),     // For source builds registered on the machine ({E2A1B3C4-...})
     Custom,           // For custom engines (Unreal Source, etc.)
 }
 
+impl EngineAssociation {
+    /// Builds the association from the content of a .uproject file.
+    pub fn from_uproject(uproject_content: &serde_json::Value) -> EngineAssociation {
+        match uproject_content
+            .get("EngineAssociation")
+            .and_then(|v| v.as_str())
+        {
+            Some(value) if !value.is_empty() => Self::parse(value),
+            _ => EngineAssociation::Custom, // Default to Custom if not specified
+        }
+    }
+
+    /// Parses a non-empty EngineAssociation value, telling GUIDs apart from engine versions.
+    pub fn parse(value: &str) -> EngineAssociation {
+        if Self::is_guid(value) {
+            EngineAssociation::Guid(value.to_string())
+        } else {
+            EngineAssociation::Standard(value.to_string())
+        }
+    }
+
+    /// Checks if a value is a GUID, with or without braces and dashes.
+    pub fn is_guid(value: &str) -> bool {
+        let digits: String = value
+            .trim_start_matches('{')
+            .trim_end_matches('}')
+            .chars()
+            .filter(|c| *c != '-')
+            .collect();
+
+        digits.len() == 32 && digits.chars().all(|c| c.is_ascii_hexdigit())
+    }
+
+    /// Normalizes a GUID so that GUIDs written in different forms can be compared.
+    pub fn normalize_guid(value: &str) -> String {
+        value
+            .trim_start_matches('{')
+            .trim_end_matches('}')
+            .replace('-', "")
+            .to_ascii_uppercase()
+    }
+}
+
+impl std::fmt::Display for EngineAssociation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EngineAssociation::Standard(version) => write!(f, "{}", version),
+            EngineAssociation::Guid(guid) => write!(f, "{}", guid),
+            EngineAssociation::Custom => write!(f, "Custom"),
+        }
+    }
+}
+
+/// Fields of a project computed from its usage sessions, never stored
+const USAGE_FIELDS: [&str; 4] = ["is_open", "last_opened", "hours_this_week", "hours_this_month"];
+
 /// A project represents an Unreal Engine project with its associated metadata.
 /// It is built from the .uproject file and allows accessing various properties.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Project {
+    #[serde(default)]
+    pub id: String,          // Stable identifier (UUID), kept when the project is rescanned or relinked
     pub name: String,        // Name of the project (from .uproject file)
     pub description: String, // Description of the project (from the .uproject file)
     pub engine_association: EngineAssociation, // Engine version or "Custom" for Unreal Source
@@ -30,6 +94,18 @@ pub struct Project {
     pub plugins: Vec<ProjectPlugin>, // List of plugins associated with the project
     pub size_on_disk: u64,   // Size on disk in bytes
     pub last_scan_date: u64, // Last scan date of the project
+    #[serde(default)]
+    pub is_open: bool,       // Indicates if an editor, game or server process launched by the app is running
+    #[serde(default)]
+    pub last_opened: Option<u64>, // Start date of the last usage session (seconds since UNIX epoch)
+    #[serde(default)]
+    pub hours_this_week: f64,     // Hours spent in the project since Monday
+    #[serde(default)]
+    pub hours_this_month: f64,    // Hours spent in the project since the first day of the month
+    #[serde(default)]
+    pub missing: bool,            // The project file has been deleted or moved out of its library folder
+    #[serde(default)]
+    pub file_hashes: HashMap<String, String>, // SHA-256 of the project file and config files, to find the project after a move
 }
 
 impl Project {
@@ -46,17 +122,7 @@ impl Project {
             .unwrap_or_default()
             .to_string();
 
-        let engine_association = {
-            if let Some(engine_version) = uproject_content.get("EngineAssociation") {
-                if engine_version.is_string() && !engine_version.as_str().unwrap().is_empty() {
-                    EngineAssociation::Standard(engine_version.as_str().unwrap().to_string())
-                } else {
-                    EngineAssociation::Custom
-                }
-            } else {
-                EngineAssociation::Custom // Default to Custom if not specified
-            }
-        };
+        let engine_association = EngineAssociation::from_uproject(&uproject_content);
 
         // Fetch the project name from the file name
         let name = path
@@ -78,6 +144,7 @@ impl Project {
         let plugins = Self::discover_plugins(path, &uproject_content)?;
 
         Ok(Project {
+            id: Self::new_id(path),
             name,
             description,
             engine_association,
@@ -89,9 +156,59 @@ impl Project {
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap_or_default()
                 .as_secs(),
+            is_open: false,
+            last_opened: None,
+            hours_this_week: 0.0,
+            hours_this_month: 0.0,
+            missing: false,
+            file_hashes: Self::hash_identity_files(path),
         })
     }
 
+    /// Identifier of a new project: the ProjectID of its Config/DefaultGame.ini if it has one,
+    /// so a project removed and discovered again keeps its identifier, a random UUID otherwise
+    pub fn new_id(path: &Path) -> String {
+        let project_id = path
+            .parent()
+            .and_then(|project_dir| std::fs::read_to_string(project_dir.join("Config").join("DefaultGame.ini")).ok())
+            .and_then(|config| {
+                config.lines().find_map(|line| {
+                    let (key, value) = line.split_once('=')?;
+                    key.trim().eq_ignore_ascii_case("ProjectID").then(|| value.trim().to_string())
+                })
+            })
+            .and_then(|value| Uuid::parse_str(&value).ok())
+            .filter(|id| !id.is_nil());
+
+        project_id.unwrap_or_else(Uuid::new_v4).hyphenated().to_string()
+    }
+
+    /// Give a random identifier to a project whose identifier is used by another project
+    /// (a copy of a project folder has the same ProjectID)
+    pub fn ensure_unique_id(&mut self, projects: &[Project]) {
+        if self.id.is_empty() || projects.iter().any(|p| p.id == self.id && p.path != self.path) {
+            self.id = Uuid::new_v4().hyphenated().to_string();
+        }
+    }
+
+    /// Hash the files recognizing a project: the project file (keyed `PROJECT_FILE_HASH_KEY`)
+    /// and the `PROJECT_IDENTITY_FILES` of its folder. Missing files are skipped.
+    pub fn hash_identity_files(path: &Path) -> HashMap<String, String> {
+        let project_dir = path.parent().unwrap_or(Path::new(""));
+        let files = std::iter::once((env::PROJECT_FILE_HASH_KEY.to_string(), path.to_path_buf())).chain(
+            env::PROJECT_IDENTITY_FILES
+                .iter()
+                .map(|file| (file.to_string(), project_dir.join(file))),
+        );
+
+        files
+            .filter_map(|(key, file)| {
+                let contents = std::fs::read(file).ok()?;
+                Some((key, format!("{:x}", Sha256::digest(&contents))))
+            })
+            .collect()
+    }
+
     /// Discovers plugins from both the project's Plugins folder and the .uproject file using glob for efficiency
     fn discover_plugins(
         project_path: &PathBuf,
@@ -185,9 +302,12 @@ impl Project {
 
             // Check if the project already exists
             if let Some(existing_project) = projects.iter_mut().find(|p| p.path == project.path) {
-                *existing_project = project.clone(); // Update the existing project
+                // Update the existing project, it keeps its identifier
+                *existing_project = Project { id: existing_project.id.clone(), ..project };
             } else {
-                projects.push(project.clone()); // Else, add the project
+                let mut project = project;
+                project.ensure_unique_id(&projects);
+                projects.push(project); // Else, add the project
             }
         }
 
@@ -197,7 +317,13 @@ impl Project {
             Err(e) => return Err(Box::new(e)),
         };
 
-        let projects_json = serde_json::to_value(projects)?;
+        // The usage fields are computed when the projects are read (see `get_projects_with_usage`)
+        let mut projects_json = serde_json::to_value(projects)?;
+        for project in projects_json.as_array_mut().into_iter().flatten().filter_map(|p| p.as_object_mut()) {
+            for field in USAGE_FIELDS {
+                project.remove(field);
+            }
+        }
         store.set(env::STORE_PROJECTS_KEY, projects_json);
         store.save()?;
 
@@ -207,14 +333,20 @@ impl Project {
         Ok(())
     }
 
-    /// Scan plugins for specific projects
+    /// Scan plugins for specific projects.
+    /// Nothing is saved if the scan is cancelled before the end.
     pub fn scan_project_plugins(
         app_handle: &tauri::AppHandle,
         project_paths: &[PathBuf],
+        cancellation: &CancellationToken,
     ) -> Result<(), Box<dyn std::error::Error>> {
         let mut projects = Project::get_projects(app_handle)?;
 
         for project_path in project_paths {
+            if cancellation.is_cancelled() {
+                return Err("Plugin scanning cancelled".into());
+            }
+
             if let Some(existing_project) = projects.iter_mut().find(|p| p.path == *project_path) {
                 // Re-discover plugins for this project
                 let contents = std::fs::read_to_string(project_path)?;
@@ -237,11 +369,12 @@ impl Project {
     /// Refresh plugins for all tracked projects
     pub fn refresh_all_plugins(
         app_handle: &tauri::AppHandle,
+        cancellation: &CancellationToken,
     ) -> Result<(), Box<dyn std::error::Error>> {
         let projects = Project::get_projects(app_handle)?;
         let project_paths: Vec<PathBuf> = projects.iter().map(|p| p.path.clone()).collect();
 
-        Self::scan_project_plugins(app_handle, &project_paths)?;
+        Self::scan_project_plugins(app_handle, &project_paths, cancellation)?;
 
         Ok(())
     }
@@ -257,16 +390,51 @@ impl Project {
         let projects_list: Vec<Project> = serde_json::from_value::<Vec<Project>>(
             store.get(env::STORE_PROJECTS_KEY).unwrap_or(json!([])),
         )
-        .unwrap_or_else(|e| {
+        .map_err(|e| {
+            // The projects are never replaced by an empty list, saving it would lose them
             error!("Error parsing projects from store: {}", e);
             log(
                 &app_handle,
                 ErrorLevel::Error,
                 &format!("Error parsing projects from store: {}", e),
             );
+            e
+        })?;
+
+        Ok(projects_list)
+    }
+
+    /// The tracked projects with their usage (open state and hours), as shown to the user.
+    /// The usage is computed from the processes and sessions, it is not stored with the projects.
+    pub fn get_projects_with_usage(
+        app_handle: &tauri::AppHandle,
+    ) -> Result<Vec<Project>, Box<dyn std::error::Error>> {
+        let mut projects_list = Self::get_projects(app_handle)?;
+
+        // Flag the projects that have a running process launched by the app
+        // and aggregate their usage sessions
+        let supervisor = app_handle.try_state::<ProcessSupervisor>();
+        let running = supervisor
+            .as_ref()
+            .map(|supervisor| supervisor.running())
+            .unwrap_or_default();
+        let sessions = ProjectSession::get_sessions(app_handle).unwrap_or_else(|e| {
+            error!("Error parsing project sessions from store: {}", e);
             vec![]
         });
 
+        for project in projects_list.iter_mut() {
+            project.is_open = supervisor
+                .as_ref()
+                .map(|supervisor| supervisor.is_project_open(&project.path))
+                .unwrap_or(false);
+
+            let usage = ProjectUsageStats::compute(&project.id, &sessions, &running);
+            project.last_opened = usage.last_opened;
+            project.hours_this_week = usage.hours_this_week;
+            project.hours_this_month = usage.hours_this_month;
+        }
+
         Ok(projects_list)
     }
 
@@ -297,7 +465,9 @@ impl Project {
         // Check if the project already exists
         for project in projects {
             if !known_projects.iter().any(|p| p.path == project.path) {
-                known_projects.push(project.clone());
+                let mut project = project.clone();
+                project.ensure_unique_id(&known_projects);
+                known_projects.push(project);
             }
         }
 
@@ -306,6 +476,43 @@ impl Project {
         Ok(())
     }
 
+    /// Path of a project from its identifier or its path. The commands taking
+    /// a project path also accept the identifier of a tracked project.
+    pub fn resolve_path(app_handle: &tauri::AppHandle, project: &str) -> PathBuf {
+        if Uuid::parse_str(project).is_ok() {
+            let tracked = Project::get_projects(app_handle)
+                .ok()
+                .and_then(|projects| projects.into_iter().find(|p| p.id == project));
+            if let Some(tracked) = tracked {
+                return tracked.path;
+            }
+        }
+
+        PathBuf::from(project)
+    }
+
+    /// Identifier of a tracked project from its identifier or its path.
+    /// The data of the projects (launch profiles, backups, sessions) is keyed by this identifier.
+    pub fn resolve_id(
+        app_handle: &tauri::AppHandle,
+        project: &str,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        Project::get_projects(app_handle)?
+            .into_iter()
+            .find(|p| p.id == project || p.path == Path::new(project))
+            .map(|p| p.id)
+            .ok_or_else(|| format!("{} is not a tracked project", project).into())
+    }
+
+    /// Identifier of the project tracked at a path, if any
+    pub fn find_id(app_handle: &tauri::AppHandle, project_path: &Path) -> Option<String> {
+        Project::get_projects(app_handle)
+            .ok()?
+            .into_iter()
+            .find(|p| p.path == project_path)
+            .map(|p| p.id)
+    }
+
     pub fn remove_projects(
         app_handle: &tauri::AppHandle,
         project_paths: &[PathBuf],
@@ -326,7 +533,7 @@ impl Project {
         app_handle.emit(
             env::EVENT_PROJECTS_UPDATED,
             ProjectsUpdatedPayload {
-                projects: Project::get_projects(app_handle)?,
+                projects: Project::get_projects_with_usage(app_handle)?,
             },
         )?;
         Ok(())
@@ -0,0 +1,234 @@
+use crate::env;
+use crate::projects::actions::process_supervisor::ProcessSupervisor;
+use crate::projects::models::process::{ProcessKind, RunningProcess};
+use chrono::{Datelike, Local, NaiveTime, TimeZone};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::Manager;
+use tauri_plugin_store::StoreExt;
+
+/// A finished usage session of a project (an editor, game or server process that has exited)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectSession {
+    pub project_id: String,             // Project the session belongs to (see `Project::id`)
+    pub kind: ProcessKind,              // What has been launched
+    pub started_at: u64,                // Start date (seconds since UNIX epoch)
+    pub ended_at: u64,                  // End date (seconds since UNIX epoch)
+    pub duration_secs: u64,             // Duration of the session in seconds
+    pub command_line: String,           // Command line the process has been launched with
+    pub engine_version: Option<String>, // Version of the engine used (None if unknown)
+}
+
+/// Aggregated usage statistics of a project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectUsageStats {
+    pub project_id: String,
+    pub hours_this_week: f64,      // Hours spent since Monday 00:00 (local time)
+    pub hours_this_month: f64,     // Hours spent since the first day of the month (local time)
+    pub total_hours: f64,          // Hours spent since the sessions are recorded
+    pub session_count: usize,      // Number of sessions (running ones included)
+    pub last_opened: Option<u64>,  // Start date of the last session (seconds since UNIX epoch)
+}
+
+impl ProjectSession {
+    /// Builds the session of a process that has exited
+    pub fn from_process(process: &RunningProcess, ended_at: u64) -> Option<ProjectSession> {
+        // IDE sessions are not project usage
+        if process.kind == ProcessKind::Ide {
+            return None;
+        }
+
+        Some(ProjectSession {
+            project_id: process.project_id.clone()?,
+            kind: process.kind.clone(),
+            started_at: process.started_at,
+            ended_at,
+            duration_secs: ended_at.saturating_sub(process.started_at),
+            command_line: process.command_line.clone(),
+            engine_version: process.engine_version.clone(),
+        })
+    }
+
+    pub fn get_sessions(
+        app_handle: &tauri::AppHandle,
+    ) -> Result<Vec<ProjectSession>, Box<dyn std::error::Error>> {
+        let store = app_handle.store(env::STORE_FILE_NAME)?;
+
+        let sessions = serde_json::from_value::<Vec<ProjectSession>>(
+            store.get(env::STORE_SESSIONS_KEY).unwrap_or(json!([])),
+        )?;
+
+        Ok(sessions)
+    }
+
+    pub fn save_sessions(
+        app_handle: &tauri::AppHandle,
+        sessions: &[ProjectSession],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let store = app_handle.store(env::STORE_FILE_NAME)?;
+        store.set(env::STORE_SESSIONS_KEY, serde_json::to_value(sessions)?);
+        store.save()?;
+
+        Ok(())
+    }
+
+    /// Adds a finished session to the store
+    pub fn record(
+        app_handle: &tauri::AppHandle,
+        session: ProjectSession,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let supervisor = app_handle.state::<ProcessSupervisor>();
+        let _sessions_guard = supervisor.sessions.lock().unwrap();
+        let mut sessions = Self::get_sessions(app_handle)?;
+        sessions.push(session);
+        if sessions.len() > env::SESSIONS_MAX_RECORDS {
+            sessions.drain(..sessions.len() - env::SESSIONS_MAX_RECORDS);
+        }
+
+        Self::save_sessions(app_handle, &sessions)
+    }
+
+    /// Moves the sessions of a project to another project
+    pub fn move_to_project(
+        app_handle: &tauri::AppHandle,
+        from_id: &str,
+        into_id: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let supervisor = app_handle.state::<ProcessSupervisor>();
+        let _sessions_guard = supervisor.sessions.lock().unwrap();
+        let mut sessions = Self::get_sessions(app_handle)?;
+
+        let mut moved = false;
+        for session in sessions.iter_mut().filter(|session| session.project_id == from_id) {
+            session.project_id = into_id.to_string();
+            moved = true;
+        }
+        if moved {
+            Self::save_sessions(app_handle, &sessions)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl ProjectUsageStats {
+    /// Aggregates the sessions of a project. Running processes count as sessions ending now.
+    pub fn compute(
+        project_id: &str,
+        sessions: &[ProjectSession],
+        running: &[RunningProcess],
+    ) -> ProjectUsageStats {
+        let now = Local::now();
+
+        let running_sessions = running
+            .iter()
+            .filter_map(|process| ProjectSession::from_process(process, now.timestamp() as u64));
+        let project_sessions: Vec<ProjectSession> = sessions
+            .iter()
+            .cloned()
+            .chain(running_sessions)
+            .filter(|session| session.project_id == project_id)
+            .collect();
+
+        // Local midnight of the first day of the week (Monday) and of the month
+        let today = now.date_naive();
+        let week_start = today - chrono::Duration::days(today.weekday().num_days_from_monday() as i64);
+        let month_start = today.with_day(1).unwrap_or(today);
+        let to_timestamp = |date: chrono::NaiveDate| {
+            Local
+                .from_local_datetime(&date.and_time(NaiveTime::MIN))
+                .earliest()
+                .map(|datetime| datetime.timestamp() as u64)
+                .unwrap_or(0)
+        };
+        let (week_start, month_start) = (to_timestamp(week_start), to_timestamp(month_start));
+
+        // Sessions running at the same time (a server and its clients, an editor and a game)
+        // are counted once
+        let usage_periods = merge_sessions(&project_sessions);
+
+        // Only count the part of the periods after the start of the period
+        let seconds_since = |period_start: u64| -> u64 {
+            usage_periods
+                .iter()
+                .filter(|(_, ended_at)| *ended_at > period_start)
+                .map(|(started_at, ended_at)| ended_at - (*started_at).max(period_start))
+                .sum()
+        };
+
+        ProjectUsageStats {
+            project_id: project_id.to_string(),
+            hours_this_week: seconds_since(week_start) as f64 / 3600.0,
+            hours_this_month: seconds_since(month_start) as f64 / 3600.0,
+            total_hours: seconds_since(0) as f64 / 3600.0,
+            session_count: project_sessions.len(),
+            last_opened: project_sessions.iter().map(|session| session.started_at).max(),
+        }
+    }
+}
+
+/// Merges the overlapping sessions into periods of usage, sorted by start date
+fn merge_sessions(sessions: &[ProjectSession]) -> Vec<(u64, u64)> {
+    let mut intervals: Vec<(u64, u64)> = sessions
+        .iter()
+        .map(|session| (session.started_at, session.ended_at.max(session.started_at)))
+        .collect();
+    intervals.sort_unstable();
+
+    let mut periods: Vec<(u64, u64)> = Vec::with_capacity(intervals.len());
+    for (started_at, ended_at) in intervals {
+        match periods.last_mut() {
+            Some(last) if started_at <= last.1 => last.1 = last.1.max(ended_at),
+            _ => periods.push((started_at, ended_at)),
+        }
+    }
+
+    periods
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROJECT: &str = "3f6c1a52-8d2e-4b7a-9c41-5e0d2f8a6b13";
+
+    fn session(kind: ProcessKind, started_at: u64, ended_at: u64) -> ProjectSession {
+        ProjectSession {
+            project_id: PROJECT.to_string(),
+            kind,
+            started_at,
+            ended_at,
+            duration_secs: ended_at - started_at,
+            command_line: String::new(),
+            engine_version: None,
+        }
+    }
+
+    #[test]
+    fn counts_overlapping_sessions_once() {
+        // An editor open for two hours, and a game launched from it an hour later
+        let sessions = vec![
+            session(ProcessKind::Editor, 3600, 3 * 3600),
+            session(ProcessKind::Game, 2 * 3600, 4 * 3600),
+            session(ProcessKind::Editor, 10 * 3600, 11 * 3600),
+        ];
+
+        let stats = ProjectUsageStats::compute(PROJECT, &sessions, &[]);
+
+        assert_eq!(stats.total_hours, 4.0);
+        assert_eq!(stats.session_count, 3);
+        assert_eq!(stats.last_opened, Some(10 * 3600));
+    }
+
+    #[test]
+    fn merges_nested_and_adjacent_sessions() {
+        let sessions = vec![
+            session(ProcessKind::Server, 100, 1000),
+            session(ProcessKind::Game, 200, 300),
+            session(ProcessKind::Game, 1000, 1500),
+            session(ProcessKind::Editor, 2000, 2100),
+        ];
+
+        assert_eq!(merge_sessions(&sessions), vec![(100, 1500), (2000, 2100)]);
+    }
+}
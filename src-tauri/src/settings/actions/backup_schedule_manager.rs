@@ -0,0 +1,55 @@
+use crate::misc::errors::Verror::MessageError;
+use crate::misc::errors::Result;
+use crate::projects::models::backup::BackupState;
+use crate::projects::models::project::Project;
+use crate::settings::actions::settings_manager;
+use crate::settings::models::settings::BackupSchedule;
+use log::info;
+use tauri::{command, AppHandle};
+
+/// Get the backup schedule of a project
+#[command]
+pub fn get_backup_schedule(app_handle: AppHandle, project_path: String) -> Result<Option<BackupSchedule>> {
+    let project_id = Project::resolve_id(&app_handle, &project_path)?;
+    let settings = settings_manager::load_settings(&app_handle)?;
+
+    Ok(settings.backups.project_schedules.get(&project_id).cloned())
+}
+
+/// Create or replace the backup schedule of a project
+#[command]
+pub fn save_backup_schedule(
+    app_handle: AppHandle,
+    project_path: String,
+    schedule: BackupSchedule,
+) -> Result<()> {
+    let project_id = Project::resolve_id(&app_handle, &project_path)?;
+    if schedule.destination_path.trim().is_empty() {
+        return Err(MessageError("A backup schedule must have a destination directory".to_string()));
+    }
+    schedule.frequency.cron_schedule()?;
+    schedule.options.resolve(&schedule.compression_algorithm)?;
+
+    let mut settings = settings_manager::load_settings(&app_handle)?;
+    settings.backups.project_schedules.insert(project_id, schedule);
+
+    settings_manager::store_settings(&app_handle, &settings)?;
+    info!("Backup schedule saved successfully");
+
+    Ok(())
+}
+
+/// Delete the backup schedule of a project. The archives it created are kept.
+#[command]
+pub fn delete_backup_schedule(app_handle: AppHandle, project_path: String) -> Result<()> {
+    let project_id = Project::resolve_id(&app_handle, &project_path)?;
+    let mut settings = settings_manager::load_settings(&app_handle)?;
+    settings.backups.project_schedules.remove(&project_id);
+    settings_manager::store_settings(&app_handle, &settings)?;
+
+    let mut states = BackupState::get_states(&app_handle)?;
+    states.retain(|state| state.project_id != project_id);
+    BackupState::save_states(&app_handle, &states)?;
+
+    Ok(())
+}
@@ -0,0 +1,169 @@
+use crate::misc::errors::Verror::MessageError;
+use crate::misc::errors::{ErrorLevel, Result};
+use crate::misc::prelude::log;
+use crate::projects::models::project::Project;
+use crate::settings::actions::settings_manager;
+use crate::settings::models::settings::LaunchProfile;
+use log::{error, info};
+use tauri::{command, AppHandle};
+
+/// Get the launch profiles of a project
+#[command]
+pub fn get_launch_profiles(app_handle: AppHandle, project_path: String) -> Result<Vec<LaunchProfile>> {
+    let project_id = Project::resolve_id(&app_handle, &project_path)?;
+    let settings = settings_manager::load_settings(&app_handle)?;
+
+    Ok(settings
+        .launch_profiles
+        .project_profiles
+        .get(&project_id)
+        .cloned()
+        .unwrap_or_default())
+}
+
+/// Create or replace (same name) a launch profile of a project
+#[command]
+pub fn save_launch_profile(
+    app_handle: AppHandle,
+    project_path: String,
+    profile: LaunchProfile,
+) -> Result<()> {
+    let project_id = Project::resolve_id(&app_handle, &project_path)?;
+    if profile.name.trim().is_empty() {
+        return Err(MessageError("A launch profile must have a name".to_string()));
+    }
+
+    let mut settings = settings_manager::load_settings(&app_handle)?;
+    let profiles = settings
+        .launch_profiles
+        .project_profiles
+        .entry(project_id)
+        .or_default();
+
+    merge_profiles(profiles, vec![profile]);
+
+    settings_manager::store_settings(&app_handle, &settings)?;
+    info!("Launch profile saved successfully");
+
+    Ok(())
+}
+
+/// Delete a launch profile of a project
+#[command]
+pub fn delete_launch_profile(
+    app_handle: AppHandle,
+    project_path: String,
+    profile_name: String,
+) -> Result<()> {
+    let project_id = Project::resolve_id(&app_handle, &project_path)?;
+    let mut settings = settings_manager::load_settings(&app_handle)?;
+
+    if let Some(profiles) = settings.launch_profiles.project_profiles.get_mut(&project_id) {
+        profiles.retain(|p| p.name != profile_name);
+
+        if profiles.is_empty() {
+            settings.launch_profiles.project_profiles.remove(&project_id);
+        }
+    }
+
+    settings_manager::store_settings(&app_handle, &settings)?;
+
+    Ok(())
+}
+
+/// Export the launch profiles of a project to a JSON file to share them
+#[command]
+pub fn export_launch_profiles(
+    app_handle: AppHandle,
+    project_path: String,
+    file_path: String,
+) -> Result<()> {
+    let profiles = get_launch_profiles(app_handle.clone(), project_path)?;
+
+    let contents = serde_json::to_string_pretty(&profiles)?;
+    std::fs::write(&file_path, contents)?;
+
+    log(
+        &app_handle,
+        ErrorLevel::Info,
+        &format!("Exported {} launch profile(s) to {}", profiles.len(), file_path),
+    );
+
+    Ok(())
+}
+
+/// Import launch profiles from a JSON file into a project.
+/// Imported profiles replace the existing profiles with the same name.
+#[command]
+pub fn import_launch_profiles(
+    app_handle: AppHandle,
+    project_path: String,
+    file_path: String,
+) -> Result<Vec<LaunchProfile>> {
+    let project_id = Project::resolve_id(&app_handle, &project_path)?;
+    let contents = std::fs::read_to_string(&file_path)?;
+    let imported: Vec<LaunchProfile> = match serde_json::from_str(&contents) {
+        Ok(imported) => imported,
+        Err(e) => {
+            let error_msg = format!("Invalid launch profiles file {}: {}", file_path, e);
+            error!("{}", error_msg);
+            log(&app_handle, ErrorLevel::Error, &error_msg);
+            return Err(MessageError(error_msg));
+        }
+    };
+
+    let mut settings = settings_manager::load_settings(&app_handle)?;
+    let profiles = settings
+        .launch_profiles
+        .project_profiles
+        .entry(project_id)
+        .or_default();
+
+    let imported_count = imported.len();
+    merge_profiles(profiles, imported);
+    let result = profiles.clone();
+
+    settings_manager::store_settings(&app_handle, &settings)?;
+
+    log(
+        &app_handle,
+        ErrorLevel::Info,
+        &format!("Imported {} launch profile(s) from {}", imported_count, file_path),
+    );
+
+    Ok(result)
+}
+
+/// Find a launch profile of a project (identifier or path) by name
+pub fn find_launch_profile(
+    app_handle: &AppHandle,
+    project_path: &str,
+    profile_name: &str,
+) -> Result<LaunchProfile> {
+    let project_id = Project::resolve_id(app_handle, project_path)?;
+    let settings = settings_manager::load_settings(app_handle)?;
+
+    settings
+        .launch_profiles
+        .project_profiles
+        .get(&project_id)
+        .and_then(|profiles| profiles.iter().find(|p| p.name == profile_name))
+        .cloned()
+        .ok_or_else(|| {
+            MessageError(format!(
+                "No launch profile named \"{}\" for project {}",
+                profile_name, project_path
+            ))
+        })
+}
+
+/// Add profiles to a list, replacing the profiles with the same name
+pub fn merge_profiles(profiles: &mut Vec<LaunchProfile>, new_profiles: Vec<LaunchProfile>) {
+    for profile in new_profiles {
+        if let Some(existing) = profiles.iter_mut().find(|p| p.name == profile.name) {
+            *existing = profile;
+        } else {
+            profiles.push(profile);
+        }
+    }
+}
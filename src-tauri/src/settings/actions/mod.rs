@@ -1,2 +1,4 @@
 pub mod settings_manager;
-pub mod autostart_manager;
\ No newline at end of file
+pub mod autostart_manager;
+pub mod launch_profile_manager;
+pub mod backup_schedule_manager;
\ No newline at end of file
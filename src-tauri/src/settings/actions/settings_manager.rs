@@ -1,5 +1,6 @@
 use crate::env;
 use crate::misc::errors;
+use crate::projects::actions::library_watcher;
 use crate::settings::models::settings::AppSettings;
 use log::{error, info};
 use std::sync::Arc;
@@ -26,6 +27,11 @@ pub fn save_settings(app_handle: AppHandle, settings: AppSettings) -> Result<(),
     match store_settings(&app_handle, &settings) {
         Ok(_) => {
             info!("Settings saved successfully");
+
+            // Library folders may have been added or removed
+            if let Err(e) = library_watcher::watch_library_roots(&app_handle) {
+                error!("Failed to update the watched library folders: {}", e);
+            }
             Ok(())
         }
         Err(e) => {
@@ -40,11 +46,11 @@ pub fn load_settings(app_handle: &AppHandle) -> errors::Result<AppSettings> {
     let store: Arc<Store<Wry>> = app_handle.store(env::STORE_FILE_NAME)?;
     
     let settings = if let Some(settings_value) = store.get(env::STORE_SETTINGS_KEY) {
-        serde_json::from_value::<AppSettings>(settings_value.clone())
-            .unwrap_or_else(|e| {
-                error!("Failed to parse settings from store: {}, using defaults", e);
-                AppSettings::default()
-            })
+        // The settings are never replaced by the defaults, saving them would lose the user's settings
+        serde_json::from_value::<AppSettings>(settings_value.clone()).map_err(|e| {
+            error!("Failed to parse settings from store: {}", e);
+            e
+        })?
     } else {
         info!("No settings found in store, using defaults");
         AppSettings::default()
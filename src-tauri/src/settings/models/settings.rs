@@ -1,3 +1,7 @@
+use crate::misc::tasks::TaskKind;
+use crate::projects::actions::project_compressor::{CompressionAlgorithm, CompressionOptions};
+use crate::projects::models::archive::ArchiveRules;
+use crate::projects::models::backup::CronSchedule;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -9,6 +13,14 @@ pub struct AppSettings {
     pub cleaning_defaults: CleaningDefaults,
     pub general: GeneralSettings,
     pub compression: CompressionSettings,
+    #[serde(default)]
+    pub launch_profiles: LaunchProfiles,
+    #[serde(default)]
+    pub tasks: TaskSettings,
+    #[serde(default)]
+    pub backups: BackupSettings,
+    #[serde(default)]
+    pub discovery: DiscoverySettings,
 }
 
 /// IDE program settings - only custom programs
@@ -52,6 +64,117 @@ pub struct CompressionSettings {
     pub custom_presets: HashMap<String, String>,
 }
 
+/// Project discovery settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DiscoverySettings {
+    pub excluded_patterns: Vec<String>, // Globs of the directories not searched, relative to the searched folder (e.g. "**/Backups")
+    #[serde(default)]
+    pub library_roots: Vec<String>,     // Folders watched for projects while the app is running (see `library_watcher`)
+}
+
+/// Background task scheduling settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskSettings {
+    pub concurrency_limits: HashMap<TaskKind, usize>, // Maximum number of running tasks of each kind (unlimited if missing)
+}
+
+/// Backup schedules of each project, keyed by the project identifier (see `Project::id`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BackupSettings {
+    pub project_schedules: HashMap<String, BackupSchedule>,
+}
+
+/// Archives created automatically by the running application
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupSchedule {
+    pub enabled: bool,
+    pub frequency: BackupFrequency,
+    pub destination_path: String,                 // Directory the archives are written to
+    pub compression_algorithm: CompressionAlgorithm,
+    #[serde(default)]
+    pub rules: ArchiveRules,                      // Files to archive (the whole project by default)
+    #[serde(default)]
+    pub max_volume_size: Option<u64>,             // Split the archives into volumes of at most this size in bytes
+    #[serde(default)]
+    pub options: CompressionOptions,              // Compression level, threads and store only mode
+    #[serde(default)]
+    pub retention: RetentionPolicy,
+}
+
+/// When a scheduled backup runs (local time)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum BackupFrequency {
+    Daily { hour: u32, minute: u32 },
+    Weekly { weekday: u32, hour: u32, minute: u32 }, // Weekday from 0 (Sunday) to 6 (Saturday)
+    Cron { expression: String },                     // "minute hour day-of-month month day-of-week"
+}
+
+/// Archives of a schedule kept after a backup, the others are deleted.
+/// An archive is kept if any rule keeps it; nothing is deleted when no rule is set.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    pub keep_last: Option<usize>,   // Keep the N most recent archives
+    pub keep_daily: Option<u32>,    // Keep the most recent archive of each of the last N days
+    pub keep_weekly: Option<u32>,   // Keep the most recent archive of each of the last N weeks
+}
+
+/// Launch profiles of each project, keyed by the project identifier (see `Project::id`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LaunchProfiles {
+    pub project_profiles: HashMap<String, Vec<LaunchProfile>>,
+}
+
+/// A named set of options used to build the command line of a launch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LaunchProfile {
+    pub name: String,
+    pub mode: LaunchMode,
+    pub map: Option<String>,                   // Startup map (e.g. /Game/Maps/MainMenu)
+    pub log: bool,                             // Open a log window (-log)
+    pub null_rhi: bool,                        // Run without rendering (-nullrhi)
+    pub extra_arguments: Vec<String>,          // Additional command-line arguments
+    pub environment: HashMap<String, String>,  // Environment variables set for the launched process
+    pub working_directory: Option<String>,     // Working directory (defaults to the project directory)
+}
+
+/// How the project is started by a launch profile
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum LaunchMode {
+    Editor, // Open the editor
+    Game,   // Run the project as a standalone game (-game)
+    Server, // Run the project as a dedicated server (-server)
+}
+
+impl LaunchProfile {
+    /// Builds the command-line arguments of this profile for a project
+    pub fn arguments(&self, project_path: &std::path::Path) -> Vec<String> {
+        let mut arguments = vec![project_path.display().to_string()];
+
+        // The startup map must directly follow the project path
+        if let Some(map) = self.map.as_ref().filter(|map| !map.is_empty()) {
+            arguments.push(map.clone());
+        }
+
+        match self.mode {
+            LaunchMode::Editor => {}
+            LaunchMode::Game => arguments.push("-game".to_string()),
+            LaunchMode::Server => arguments.push("-server".to_string()),
+        }
+
+        if self.log {
+            arguments.push("-log".to_string());
+        }
+
+        if self.null_rhi {
+            arguments.push("-nullrhi".to_string());
+        }
+
+        arguments.extend(self.extra_arguments.iter().cloned());
+
+        arguments
+    }
+}
+
 impl Default for AppSettings {
     fn default() -> Self {
         Self {
@@ -60,6 +183,23 @@ impl Default for AppSettings {
             cleaning_defaults: CleaningDefaults::default(),
             general: GeneralSettings::default(),
             compression: CompressionSettings::default(),
+            launch_profiles: LaunchProfiles::default(),
+            tasks: TaskSettings::default(),
+            backups: BackupSettings::default(),
+            discovery: DiscoverySettings::default(),
+        }
+    }
+}
+
+impl BackupFrequency {
+    /// Cron schedule equivalent to the frequency
+    pub fn cron_schedule(&self) -> crate::misc::errors::Result<CronSchedule> {
+        match self {
+            BackupFrequency::Daily { hour, minute } => CronSchedule::parse(&format!("{} {} * * *", minute, hour)),
+            BackupFrequency::Weekly { weekday, hour, minute } => {
+                CronSchedule::parse(&format!("{} {} * * {}", minute, hour, weekday))
+            }
+            BackupFrequency::Cron { expression } => CronSchedule::parse(expression),
         }
     }
 }
@@ -80,6 +220,37 @@ impl Default for EnginePrograms {
     }
 }
 
+impl Default for LaunchProfiles {
+    fn default() -> Self {
+        Self {
+            project_profiles: HashMap::new(),
+        }
+    }
+}
+
+impl Default for TaskSettings {
+    fn default() -> Self {
+        // Heavy operations run one at a time to avoid thrashing the disk
+        let concurrency_limits = HashMap::from([
+            (TaskKind::Compress, 1),
+            (TaskKind::Extract, 1),
+            (TaskKind::Clean, 1),
+            (TaskKind::Build, 1),
+            (TaskKind::Discovery, 1),
+            (TaskKind::PluginScan, 1),
+        ]);
+
+        Self { concurrency_limits }
+    }
+}
+
+impl TaskSettings {
+    /// Returns the maximum number of running tasks of a kind (None if unlimited, a limit of 0 counts as 1)
+    pub fn concurrency_limit(&self, kind: &TaskKind) -> Option<usize> {
+        self.concurrency_limits.get(kind).map(|limit| (*limit).max(1))
+    }
+}
+
 impl Default for CleaningDefaults {
     fn default() -> Self {
         Self {
@@ -4,6 +4,7 @@ import {listen} from "@tauri-apps/api/event";
 
 // Match the backend Project structure
 export interface Project {
+    id: string // Stable identifier (UUID), accepted by the commands in place of the project path
     name: string
     description: string
     engine_association: EngineAssociation
@@ -12,11 +13,17 @@ export interface Project {
     plugins: ProjectPlugin[]
     size_on_disk: number // Size in bytes
     last_scan_date: number // Duration since UNIX epoch in seconds
+    is_open: boolean // An editor, game or server launched by the app is running
+    last_opened: number | null // Start of the last usage session, seconds since UNIX epoch
+    hours_this_week: number // Usage since Monday (local time)
+    hours_this_month: number // Usage since the first day of the month (local time)
+    missing: boolean // The project file has been deleted or moved out of its library folder
 }
 
 // Match the backend EngineAssociation enum
 export type EngineAssociation =
     | { Standard: string }
+    | { Guid: string }
     | "Custom"
 
 // Match the backend ProjectPlugin structure (updated with size, scan date, and docs URL)
@@ -172,9 +179,12 @@ export const useProjectStore = () => {
         if (typeof engineAssociation === 'string' && engineAssociation === 'Custom') {
             return 'Custom'
         }
-        if (typeof engineAssociation === 'object' && engineAssociation.Standard) {
+        if (typeof engineAssociation === 'object' && 'Standard' in engineAssociation) {
             return engineAssociation.Standard
         }
+        if (typeof engineAssociation === 'object' && 'Guid' in engineAssociation) {
+            return engineAssociation.Guid
+        }
         return 'Unknown'
     }
 
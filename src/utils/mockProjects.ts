@@ -2,6 +2,7 @@ import type { Project } from '../stores/projectStore'
 
 export const mockProjects: Project[] = [
   {
+    id: "3f6c1a52-8d2e-4b7a-9c41-5e0d2f8a6b13",
     name: "ActionRPG_Blueprint",
     description: "A third-person action RPG with magic system and inventory management. Features include character progression, quest system, and dynamic weather.",
     engine_association: { Standard: "5.3" },
@@ -55,9 +56,15 @@ export const mockProjects: Project[] = [
       }
     ],
     size_on_disk: 2147483648, // 2GB
-    last_scan_date: Math.floor(Date.now() / 1000) - 3600 // 1 hour ago
+    last_scan_date: Math.floor(Date.now() / 1000) - 3600, // 1 hour ago
+    is_open: false,
+    last_opened: null,
+    hours_this_week: 0,
+    hours_this_month: 0,
+    missing: false
   },
   {
+    id: "a1d94e07-62bc-4f3e-8a5d-0c7b19e2f456",
     name: "ShooterGame_CPP",
     description: "Multiplayer first-person shooter with advanced networking features. Includes weapon customization, team-based gameplay, and anti-cheat integration.",
     engine_association: { Standard: "5.4" },
@@ -120,9 +127,15 @@ export const mockProjects: Project[] = [
       }
     ],
     size_on_disk: 5368709120, // 5GB
-    last_scan_date: Math.floor(Date.now() / 1000) - 1800 // 30 minutes ago
+    last_scan_date: Math.floor(Date.now() / 1000) - 1800, // 30 minutes ago
+    is_open: false,
+    last_opened: null,
+    hours_this_week: 0,
+    hours_this_month: 0,
+    missing: false
   },
   {
+    id: "7b2e5c90-14af-4d68-b3e2-9f61a0c8d725",
     name: "CustomEngine_Project",
     description: "Experimental project using a custom Unreal Engine build with proprietary rendering features and custom tools for procedural content generation.",
     engine_association: "Custom",
@@ -167,9 +180,15 @@ export const mockProjects: Project[] = [
       }
     ],
     size_on_disk: 8589934592, // 8GB
-    last_scan_date: Math.floor(Date.now() / 1000) - 7200 // 2 hours ago
+    last_scan_date: Math.floor(Date.now() / 1000) - 7200, // 2 hours ago
+    is_open: false,
+    last_opened: null,
+    hours_this_week: 0,
+    hours_this_month: 0,
+    missing: false
   },
   {
+    id: "c48f0d36-9e71-4a25-8b0c-2d5e7f193a68",
     name: "MobileGame_UE4",
     description: "Cross-platform mobile puzzle game optimized for iOS and Android. Features touch controls, in-app purchases, and social integration.",
     engine_association: { Standard: "4.27" },
@@ -223,9 +242,15 @@ export const mockProjects: Project[] = [
       }
     ],
     size_on_disk: 1073741824, // 1GB
-    last_scan_date: Math.floor(Date.now() / 1000) - 86400 // 1 day ago
+    last_scan_date: Math.floor(Date.now() / 1000) - 86400, // 1 day ago
+    is_open: false,
+    last_opened: null,
+    hours_this_week: 0,
+    hours_this_month: 0,
+    missing: false
   },
   {
+    id: "e915b7a4-3c08-4f9d-a6e1-8b2c0d4f5e37",
     name: "VR_Experience",
     description: "Immersive virtual reality experience with hand tracking and haptic feedback. Designed for architectural visualization and training simulations.",
     engine_association: { Standard: "5.2" },
@@ -288,6 +313,11 @@ export const mockProjects: Project[] = [
       }
     ],
     size_on_disk: 4294967296, // 4GB
-    last_scan_date: Math.floor(Date.now() / 1000) - 300 // 5 minutes ago
+    last_scan_date: Math.floor(Date.now() / 1000) - 300, // 5 minutes ago
+    is_open: false,
+    last_opened: null,
+    hours_this_week: 0,
+    hours_this_month: 0,
+    missing: false
   }
 ]
\ No newline at end of file